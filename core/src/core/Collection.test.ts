@@ -1,10 +1,11 @@
 import { strict as assert } from "node:assert";
 import test from "node:test";
-import { Collection } from "./Collection";
-import { sumIndex, btreeIndex } from "../indexes";
+import { Collection, ConflictException, InconsistentIndexException } from "./Collection";
+import { BTreeIndex, sumIndex, btreeIndex, hashIndex, uniqueHashIndex } from "../indexes";
+import { Index, IndexContext, UnregisteredIndex, group, premap } from "./Index";
 import Long from "long";
 import { MockIndex } from "../test_util/MockIndex";
-import { UpdateType } from "./Update";
+import { Update, UpdateType } from "./Update";
 import { Id, Item } from "./simple_types";
 
 test("Collection", async (t) => {
@@ -28,6 +29,45 @@ test("Collection", async (t) => {
     assert.strictEqual(c.get(ix), 13);
   });
 
+  await test("from", () => {
+    const c = Collection.from(new Set([1, 2, 3]));
+    const sum = c.registerIndex(sumIndex());
+    assert.strictEqual(sum.value(), 6);
+    assert.deepEqual(
+      c.toList().map(([, v]) => v),
+      [1, 2, 3]
+    );
+  });
+
+  await test("addAll", () => {
+    const c = new Collection<string>();
+    const ids = c.addAll(["a", "b"]);
+    assert.deepEqual(
+      ids.map((id) => c.get(id)),
+      ["a", "b"]
+    );
+  });
+
+  await test("allocateIds", () => {
+    const leader = new Collection<string>();
+    const replica = new Collection<string>();
+
+    const [a, b] = leader.allocateIds(2);
+    assert.ok(a.compare(b) < 0);
+    leader.insertAt(a, "a");
+    leader.insertAt(b, "b");
+    replica.insertAt(a, "a");
+    replica.insertAt(b, "b");
+
+    assert.deepEqual(leader.toList(), replica.toList());
+    assert.throws(() => leader.insertAt(a, "c"));
+    assert.strictEqual(leader.get(a), "a");
+
+    const c = leader.add("c");
+    assert.ok(b.compare(c) < 0);
+    assert.ok(replica.add("c").equals(c));
+  });
+
   await test("delete non existent", () => {
     const c = new Collection<number>();
     const ix = c.add(12);
@@ -123,4 +163,514 @@ test("Collection", async (t) => {
       { type: UpdateType.DELETE, id, oldValue: 1 },
     ]);
   });
+
+  await test("watch", () => {
+    const c = new Collection<number>();
+    const sum = c.registerIndex(sumIndex());
+
+    const seen: number[] = [];
+    const unsubscribe = c.watch(() => sum.value(), (v) => seen.push(v));
+    assert.deepEqual(seen, [0]);
+
+    const id = c.add(1);
+    c.add(2);
+    assert.deepEqual(seen, [0, 1, 3]);
+
+    // Results that do not change are not reported
+    c.set(id, 1);
+    assert.deepEqual(seen, [0, 1, 3]);
+
+    unsubscribe();
+    c.add(3);
+    assert.deepEqual(seen, [0, 1, 3]);
+  });
+
+  await test("replaceAll", () => {
+    const c = new Collection<number>();
+    const sum = c.registerIndex(sumIndex());
+    const unique = c.registerIndex(uniqueHashIndex());
+    const one = c.add(1);
+    const two = c.add(2);
+
+    const seen: number[] = [];
+    c.watch(() => sum.value(), (v) => seen.push(v));
+
+    assert.deepEqual(c.replaceAll([2, 5]), [[one, 1], [two, 2]]);
+    assert.deepEqual(seen, [3, 7]);
+    assert.deepEqual(c.toList().map(([, v]) => v), [2, 5]);
+    assert.strictEqual(unique.eq(1), undefined);
+
+    // A conflict among the new values puts the old items back
+    const before = c.toList();
+    assert.throws(() => c.replaceAll([3, 3]), ConflictException);
+    assert.deepEqual(c.toList(), before);
+    assert.strictEqual(sum.value(), 7);
+    assert.deepEqual(seen, [3, 7]);
+  });
+
+  await test("replaceAll with hooks", () => {
+    const c = new Collection<number>();
+    const sum = c.registerIndex(sumIndex());
+    c.registerIndex(uniqueHashIndex());
+    c.addAll([1, 2]);
+
+    const inserted: number[] = [];
+    const removed: number[] = [];
+    c.onInsert((v) => {
+      if (v === 99) throw new Error("vetoed");
+      inserted.push(v);
+      return v;
+    });
+    c.onRemove((v) => removed.push(v));
+    let notified = 0;
+    c.watch(() => 0, () => notified++, () => false);
+    notified = 0;
+
+    // Every hook runs once, before anything changes
+    const before = c.toList();
+    assert.throws(() => c.replaceAll([3, 3]), ConflictException);
+    assert.deepEqual(removed, [1, 2]);
+    assert.deepEqual(inserted, [3, 3]);
+    assert.deepEqual(c.toList(), before);
+    assert.strictEqual(sum.value(), 3);
+    assert.strictEqual(notified, 0);
+
+    // A vetoing hook stops the swap before the indexes see it
+    assert.throws(() => c.replaceAll([4, 99]), /vetoed/);
+    assert.deepEqual(c.toList(), before);
+    assert.strictEqual(notified, 0);
+
+    c.replaceAll([5]);
+    assert.deepEqual(removed, [1, 2, 1, 2, 1, 2]);
+    assert.strictEqual(sum.value(), 5);
+    assert.strictEqual(notified, 1);
+  });
+
+  await test("splitOff", () => {
+    const c = new Collection<number>();
+    const sum = c.registerIndex(sumIndex());
+
+    const one = c.add(1);
+    const two = c.add(2);
+    const three = c.add(3);
+
+    const odd = c.splitOff((v) => v % 2 === 1);
+    assert.deepEqual(odd.toList(), [[one, 1], [three, 3]]);
+    assert.deepEqual(c.toList(), [[two, 2]]);
+    assert.strictEqual(sum.value(), 2);
+
+    // New ids do not clash with the moved ones
+    const four = odd.add(4);
+    assert.ok(four.compare(three) > 0);
+  });
+
+  await test("splitOff.batched", () => {
+    const reported: InconsistentIndexException<any>[] = [];
+    const c = Collection.from([1, 2, 3, 4], {
+      onInconsistency: (e) => reported.push(e),
+    });
+    const batches: number[] = [];
+    class CountBatches extends Index<number, number> {
+      constructor(ctx: IndexContext<number>) {
+        super(ctx);
+      }
+
+      _onUpdate(): () => void {
+        return () => {};
+      }
+
+      override _onUpdates(updates: Update<number>[]): () => void {
+        batches.push(updates.length);
+        return () => {};
+      }
+    }
+    c.registerIndex(new UnregisteredIndex((ctx) => new CountBatches(ctx)));
+
+    const odd = c.splitOff((v) => v % 2 === 1);
+    assert.deepEqual(batches, [2]);
+
+    // The new collection reports inconsistencies the same way
+    let salt = 0;
+    odd.registerIndex(group((v: number) => v + salt, hashIndex<number, number>()));
+    salt = 1;
+    odd.delete(odd.toList()[0][0]);
+    assert.strictEqual(reported.length, 1);
+
+    // Vetoed removals leave both collections unchanged
+    c.onRemove(() => {
+      throw new Error("vetoed");
+    });
+    assert.throws(() => c.splitOff(() => true), /vetoed/);
+    assert.deepEqual(c.toList().map(([, v]) => v), [2, 4]);
+  });
+
+  await test("partition", () => {
+    const c = new Collection<number>();
+    const one = c.add(1);
+    const two = c.add(2);
+
+    const [odd, even] = c.partition((v) => v % 2 === 1);
+    assert.deepEqual(odd.toList(), [[one, 1]]);
+    assert.deepEqual(even.toList(), [[two, 2]]);
+    assert.strictEqual(c.toList().length, 2);
+  });
+
+  await test("partition.options", () => {
+    const reported: InconsistentIndexException<any>[] = [];
+    const c = Collection.from([1, 2], {
+      onInconsistency: (e) => reported.push(e),
+    });
+
+    // A non-deterministic group function puts the index out of sync
+    let salt = 0;
+    for (const part of c.partition((v) => v % 2 === 1)) {
+      part.registerIndex(group((v: number) => v + salt, hashIndex<number, number>()));
+      salt = 1;
+      part.delete(part.toList()[0][0]);
+      salt = 0;
+    }
+    assert.strictEqual(reported.length, 2);
+  });
+
+  await test("map", () => {
+    const c = new Collection<number>();
+    const one = c.add(1);
+    const two = c.add(2);
+
+    const strings = c.map((v) => `${v}`);
+    assert.deepEqual(strings.toList(), [[one, "1"], [two, "2"]]);
+
+    const ix = strings.registerIndex(btreeIndex());
+    assert.deepEqual(ix.max1(), new Item(two, "2"));
+  });
+
+  await test("map.options", () => {
+    const reported: InconsistentIndexException<any>[] = [];
+    const c = Collection.from([1], {
+      onInconsistency: (e) => reported.push(e),
+    });
+    const strings = c.map((v) => `${v}`);
+
+    // A non-deterministic group function puts the index out of sync
+    let salt = "";
+    strings.registerIndex(group((v: string) => v + salt, hashIndex<string, string>()));
+    salt = "x";
+    strings.delete(strings.toList()[0][0]);
+    assert.strictEqual(reported.length, 1);
+  });
+
+  await test("adjustMany", () => {
+    const c = new Collection<number>();
+    const sum = c.registerIndex(sumIndex());
+
+    const one = c.add(1);
+    const two = c.add(2);
+    const missing = Id.fromLong(Long.fromNumber(666, true));
+
+    const adjusted = c.adjustMany([one, missing, two], (v) => v * 10);
+    assert.deepEqual(adjusted, [
+      { id: one, oldValue: 1, newValue: 10 },
+      { id: two, oldValue: 2, newValue: 20 },
+    ]);
+    assert.strictEqual(sum.value(), 30);
+
+    assert.strictEqual(c.adjust(missing, (v) => v), undefined);
+  });
+
+  await test("adjust falsy values", () => {
+    const c = new Collection<number | string | boolean>();
+    const [zero, empty, no] = c.addAll([0, "", false]);
+
+    assert.deepEqual(c.adjust(zero, (v) => (v as number) + 1), {
+      id: zero,
+      oldValue: 0,
+      newValue: 1,
+    });
+    c.adjustMany([empty, no], (v) => v);
+
+    assert.deepEqual(c.toList(), [
+      [zero, 1],
+      [empty, ""],
+      [no, false],
+    ]);
+  });
+
+  await test("deleteMany", () => {
+    const c = new Collection<number>();
+    const sum = c.registerIndex(sumIndex());
+    const byValue = c.registerIndex(btreeIndex());
+    const byParity = c.registerIndex(group((v) => v % 2, btreeIndex()));
+
+    const ids = [1, 2, 3, 3, 4].map((v) => c.add(v));
+    const missing = Id.fromLong(Long.fromNumber(666, true));
+
+    const deleted = c.deleteMany([ids[0], ids[2], missing, ids[3], ids[0]]);
+    assert.deepEqual(deleted, [1, 3, 3]);
+    assert.strictEqual(sum.value(), 6);
+    assert.deepEqual(byValue.eq(3), []);
+    assert.strictEqual(byValue.countDistinct(), 2);
+    assert.strictEqual(byParity.get(1), undefined);
+    assert.strictEqual(byParity.get(0)?.countDistinct(), 2);
+    assert.deepEqual(c.deleteMany([]), []);
+  });
+
+  await test("deleteMany rejected by an index", () => {
+    class RejectDeletes extends Index<number, number> {
+      constructor(ctx: IndexContext<number>) {
+        super(ctx);
+      }
+
+      _onUpdate(update: Update<number>): () => void {
+        if (update.type === UpdateType.DELETE) {
+          throw new Error("deletes are not allowed");
+        }
+        return () => {};
+      }
+    }
+
+    const c = Collection.from([1, 2]);
+    const sum = c.registerIndex(sumIndex());
+    c.registerIndex(new UnregisteredIndex((ctx) => new RejectDeletes(ctx)));
+
+    const before = c.toList();
+    assert.throws(() => c.deleteMany(before.map(([id]) => id)));
+    assert.deepEqual(c.toList(), before);
+    assert.strictEqual(sum.value(), 3);
+  });
+
+  await test("dedup", () => {
+    const c = Collection.from([1, 2, 1, 3, 2, 1]);
+    const ix = c.registerIndex(hashIndex<number, number>());
+    const ids = c.toList().map(([id]) => id);
+
+    const removed = c.dedup(ix.duplicates(), "first");
+    assert.deepEqual(
+      removed.map((item) => item.id.toString()).sort(),
+      [ids[2], ids[4], ids[5]].map((id) => id.toString()).sort()
+    );
+    assert.deepEqual(c.toList().map(([, v]) => v).sort(), [1, 2, 3]);
+    assert.deepEqual(ix.duplicates(), []);
+
+    const d = Collection.from([1, 2, 1]);
+    const ixD = d.registerIndex(hashIndex<number, number>());
+    const idsD = d.toList().map(([id]) => id);
+    d.dedup(ixD.duplicates(), "last");
+    assert.strictEqual(d.get(idsD[0]), undefined);
+    assert.strictEqual(d.get(idsD[2]), 1);
+
+    const e = Collection.from([5, 5, 5]);
+    const ixE = e.registerIndex(hashIndex<number, number>());
+    const idsE = e.toList().map(([id]) => id);
+    e.dedup(ixE.duplicates(), (items) => items.find((i) => i.id.equals(idsE[1]))!);
+    assert.deepEqual(e.toList().map(([id]) => id.toString()), [idsE[1].toString()]);
+  });
+
+  await test("describeIndexes", () => {
+    const c = Collection.from([1, 2, 3]);
+    c.registerIndex(group((v) => v % 2, premap((v) => v * 2, btreeIndex())));
+    c.registerIndex(hashIndex());
+
+    assert.deepEqual(c.describeIndexes(), [
+      {
+        name: "GroupedIndex",
+        parameters: { groups: 2 },
+        children: [
+          {
+            name: "PremapIndex",
+            parameters: {},
+            children: [
+              {
+                name: "BTreeIndex",
+                parameters: { distinctValues: 0, maxItemsPerValue: 0 },
+                children: [],
+              },
+            ],
+          },
+        ],
+      },
+      {
+        name: "HashIndex",
+        parameters: { distinctValues: 3, maxItemsPerValue: 1 },
+        children: [],
+      },
+    ]);
+  });
+
+  await test("stats", () => {
+    const c = Collection.from([1, 2, 3, 4, 5]);
+    c.registerNamedIndex("byParity", group((v) => v % 2, sumIndex()));
+    c.registerIndex(premap((v) => Math.min(v, 3), hashIndex()));
+    c.delete(c.toList()[0][0]);
+
+    assert.deepEqual(c.stats(), {
+      items: 4,
+      indexes: [
+        {
+          name: "byParity",
+          kind: "GroupedIndex",
+          groupSizes: new Map([[2, 2]]),
+        },
+        {
+          name: undefined,
+          kind: "PremapIndex",
+          distinctValues: 2,
+          itemsPerValue: 2,
+        },
+      ],
+    });
+  });
+
+  await test("stats.grouped", () => {
+    let registered = 0;
+    const counted = new UnregisteredIndex((ctx: IndexContext<number>) => {
+      registered++;
+      return hashIndex<number, number>()._register(ctx);
+    });
+    const c = Collection.from([1, 2, 3]);
+    c.registerIndex(group((v) => v % 2, counted));
+    const before = registered;
+
+    assert.deepEqual(c.stats().indexes[0].groupSizes, new Map([[2, 1], [1, 1]]));
+    assert.strictEqual(registered, before);
+  });
+
+  await test("named indexes", () => {
+    const c = Collection.from([3, 1, 2]);
+    const byValue = c.registerNamedIndex("byValue", btreeIndex<number, number>());
+    c.registerNamedIndex("byParity", group((v) => v % 2, hashIndex()));
+
+    assert.deepEqual(c.indexNames(), ["byValue", "byParity"]);
+    assert.strictEqual(c.namedIndex("byValue"), byValue);
+    assert.strictEqual(c.namedIndex("missing"), undefined);
+    assert.throws(
+      () => c.registerNamedIndex("byValue", hashIndex()),
+      /^Error: composable-indexes: An index named "byValue"/
+    );
+
+    const ix = c.namedIndex("byValue");
+    assert.ok(ix instanceof BTreeIndex);
+    assert.deepEqual(ix.max().map((i) => i.value), [3]);
+  });
+
+  await test("mutation hooks", () => {
+    type Doc = { text: string; version?: number };
+    const c = new Collection<Doc>();
+    const removed: string[] = [];
+
+    const stopVersioning = c.onInsert((doc, _id, pre) => ({
+      ...doc,
+      version: (pre?.version ?? 0) + 1,
+    }));
+    c.onInsert((doc) => {
+      if (doc.text === "") throw new Error("empty");
+      return doc;
+    });
+    c.onRemove((doc) => {
+      removed.push(doc.text);
+    });
+
+    const id = c.add({ text: "a" });
+    assert.deepEqual(c.get(id), { text: "a", version: 1 });
+
+    c.adjust(id, (doc) => ({ text: doc.text + "b" }));
+    assert.deepEqual(c.get(id), { text: "ab", version: 2 });
+    assert.deepEqual(removed, []);
+
+    assert.throws(() => c.set(id, { text: "" }));
+    assert.deepEqual(c.get(id), { text: "ab", version: 2 });
+
+    stopVersioning();
+    c.set(id, { text: "c" });
+    assert.deepEqual(c.get(id), { text: "c" });
+
+    const other = c.add({ text: "d" });
+    c.deleteMany([id, other]);
+    assert.deepEqual(removed, ["c", "d"]);
+  });
+
+  await test("asReadonly", () => {
+    const c = new Collection<number>();
+    const view = c.asReadonly();
+    const one = c.add(1);
+
+    assert.strictEqual(view.get(one), 1);
+    assert.deepEqual(view.toList(), [[one, 1]]);
+    assert.strictEqual("add" in view, false);
+
+    const seen: number[] = [];
+    view.watch(() => view.toList().length, (n) => seen.push(n));
+    c.add(2);
+    assert.deepEqual(seen, [1, 2]);
+  });
+
+  await test("symmetricDifference", () => {
+    const a = Collection.from([1, 2, 2, 3]);
+    const b = Collection.from([2, 3, 4]);
+
+    assert.deepEqual(a.symmetricDifference(b), {
+      onlyInThis: [1, 2],
+      onlyInOther: [4],
+    });
+    assert.strictEqual(a.contentEquals(b), false);
+    assert.strictEqual(a.contentEquals(Collection.from([3, 2, 1, 2])), true);
+
+    const people = Collection.from([{ name: "a" }, { name: "b" }]);
+    const copy = Collection.from([{ name: "b" }, { name: "a" }]);
+    assert.strictEqual(people.contentEquals(copy), false);
+    assert.strictEqual(people.contentEquals(copy, (p) => p.name), true);
+  });
+
+  await test("version", () => {
+    const c = new Collection<number>();
+    const view = c.asReadonly();
+    assert.strictEqual(c.version, 0);
+
+    const id = c.add(1);
+    c.set(id, 2);
+    assert.strictEqual(c.version, 2);
+
+    c.delete(Id.fromLong(Long.fromNumber(666, true)));
+    c.deleteMany([]);
+    assert.strictEqual(c.version, 2);
+
+    c.deleteMany([id]);
+    assert.strictEqual(view.version, 3);
+  });
+
+  await test("checkIndex", () => {
+    assert.strictEqual(
+      Collection.checkIndex(group((v: number) => v % 2, btreeIndex()), [1, 2, 3]),
+      undefined
+    );
+
+    let salt = 0;
+    const unstable = group((v: number) => v + salt++, hashIndex<number, number>());
+    const problem = Collection.checkIndex(unstable, [1, 2]);
+    assert.ok(problem instanceof InconsistentIndexException);
+  });
+
+  await test("inconsistent index", () => {
+    // A non-deterministic group function puts the index out of sync
+    let salt = 0;
+    const unstable = group((v: number) => v + salt, hashIndex<number, number>());
+
+    const strict = new Collection<number>();
+    strict.registerIndex(unstable);
+    const id = strict.add(1);
+    salt = 1;
+    assert.throws(() => strict.delete(id), InconsistentIndexException);
+
+    salt = 0;
+    const reported: InconsistentIndexException<any>[] = [];
+    const lenient = new Collection<number>({
+      onInconsistency: (e) => reported.push(e),
+    });
+    const ix = lenient.registerIndex(unstable);
+    const id2 = lenient.add(1);
+    salt = 1;
+    lenient.delete(id2);
+    assert.strictEqual(reported.length, 1);
+    assert.strictEqual(lenient.get(id2), undefined);
+    assert.strictEqual(ix.get(1)?.countDistinct(), 1);
+  });
 });
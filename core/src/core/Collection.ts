@@ -1,8 +1,9 @@
 import Long from "long";
 import { IdMap } from "../util/IdMap";
-import { Update, UpdateType } from "./Update";
-import { Id } from "..";
-import { Index, IndexContext, UnregisteredIndex } from "./Index";
+import { DeleteUpdate, Update, UpdateType } from "./Update";
+import { Id, Item } from "..";
+import { GroupedIndex, Index, IndexContext, IndexDescription, UnregisteredIndex } from "./Index";
+import type { PartitionedIndex } from "../indexes/PartitionedIndex";
 
 /**
  * Maintains a collection of items, and keeps the registered indexes up to date.
@@ -17,16 +18,106 @@ import { Index, IndexContext, UnregisteredIndex } from "./Index";
  * const collection = new Collection<Readonly<Person>>();
  * ```
  */
-export class Collection<T> {
+export class Collection<T> implements ReadonlyCollection<T> {
   private last: Id = Id.fromLong(Long.UZERO);
+  private _version = 0;
   private store: IdMap<T> = new IdMap();
 
   private indexes: Index<T, T>[] = [];
+  private namedIndexes: Map<string, Index<T, T>> = new Map();
+  private watchers: Set<() => void> = new Set();
+  // While positive, watchers are notified once at the end instead of after
+  // every update.
+  private deferWatchers = 0;
+  private insertHooks: Set<InsertHook<T>> = new Set();
+  private removeHooks: Set<RemoveHook<T>> = new Set();
+
+  private readonly onInconsistency: (e: InconsistentIndexException<any>) => void;
+  // Kept to create related collections, like the ones from `splitOff`,
+  // `partition` and `map`, with the same options.
+  private readonly options?: {
+    onInconsistency?: (e: InconsistentIndexException<any>) => void;
+  };
 
   /**
    * Creates an empty collection.
+   * 
+   * @param options.onInconsistency Called when an index finds that its state
+   * does not agree with an update, which usually means a bug in a custom index
+   * or a non-deterministic `premap`/`group` function. Defaults to throwing the
+   * {@link InconsistentIndexException}. If it returns instead, the index skips
+   * the offending part of the update, letting long-running processes degrade
+   * instead of crashing.
+   */
+  constructor(options?: {
+    onInconsistency?: (e: InconsistentIndexException<any>) => void;
+  }) {
+    this.options = options;
+    this.onInconsistency =
+      options?.onInconsistency ??
+      ((e) => {
+        throw e;
+      });
+  }
+
+  /**
+   * Creates a collection with the given items, in order.
+   * 
+   * Indexes registered afterwards are built from all the items at once, which
+   * is cheaper than updating them after each addition.
+   * 
+   * @example
+   * ```typescript
+   * const collection = Collection.from([1, 2, 3]);
+   * const ixSum = collection.registerIndex(sumIndex());
+   * ```
+   * 
+   * Complexity: O(n)
    */
-  constructor() {}
+  static from<T>(
+    values: Iterable<T>,
+    options?: ConstructorParameters<typeof Collection>[0]
+  ): Collection<T> {
+    const ret = new Collection<T>(options);
+    ret.addAll(values);
+    return ret;
+  }
+
+  /**
+   * Runs some values through an index on a scratch collection, to catch
+   * indexes that lose track of their items before they are used. This
+   * usually means a bug in a custom index, or a non-deterministic
+   * `premap`/`group` function.
+   * 
+   * Each probe is added, updated to the next probe, and deleted on its own;
+   * then all of them are added and deleted in one batch. Probes should be
+   * distinct, so that unique indexes accept them.
+   * 
+   * @example
+   * ```typescript
+   * const problem = Collection.checkIndex(myCustomIndex(), [probe1, probe2]);
+   * if (problem) throw problem;
+   * ```
+   * 
+   * @returns The first inconsistency found, or `undefined` if there is none.
+   */
+  static checkIndex<T>(
+    uIndex: UnregisteredIndex<T, T, Index<T, T>>,
+    probes: readonly T[]
+  ): InconsistentIndexException<any> | undefined {
+    const found: InconsistentIndexException<any>[] = [];
+    const c = new Collection<T>({ onInconsistency: (e) => found.push(e) });
+    c.registerIndex(uIndex);
+
+    probes.forEach((probe, i) => {
+      const id = c.add(probe);
+      c.set(id, probes[(i + 1) % probes.length]);
+      c.delete(id);
+    });
+    c.deleteMany(c.addAll(probes));
+
+    return found[0];
+  }
 
   /**
    * Registers an {@link UnregisteredIndex} to a collection, returning the
@@ -38,7 +129,10 @@ export class Collection<T> {
    * Complexity: O(n) where n is the number of items already in the collection.
    */
   registerIndex<Ix extends Index<T, T>>(uIndex: UnregisteredIndex<T, T, Ix>): Ix {
-    const ctx = new IndexContext((id) => this.store.get(id));
+    const ctx = new IndexContext(
+      (id) => this.store.get(id),
+      (e) => this.onInconsistency(e)
+    );
     const index = uIndex._register(ctx);
     this.store.forEach((elem, id) => {
       index._onUpdate({
@@ -51,6 +145,80 @@ export class Collection<T> {
     return index;
   }
 
+  /**
+   * Like {@link Collection.registerIndex}, but also makes the index available
+   * under `name`, for layers that look up indexes at runtime like a REPL or
+   * a plugin system.
+   * 
+   * @throws Error if an index is already registered under `name`.
+   */
+  registerNamedIndex<Ix extends Index<T, T>>(
+    name: string,
+    uIndex: UnregisteredIndex<T, T, Ix>
+  ): Ix {
+    if (this.namedIndexes.has(name)) {
+      throw new Error(
+        `composable-indexes: An index named "${name}" is already registered`
+      );
+    }
+    const index = this.registerIndex(uIndex);
+    this.namedIndexes.set(name, index);
+    return index;
+  }
+
+  /**
+   * The index registered under `name` with
+   * {@link Collection.registerNamedIndex}, if any. Use `instanceof` to
+   * recover its type.
+   * 
+   * @example
+   * ```typescript
+   * const ix = collection.namedIndex("byAge");
+   * if (ix instanceof BTreeIndex) {
+   *   console.log(ix.max());
+   * }
+   * ```
+   * 
+   * @group Queries
+   */
+  namedIndex(name: string): Index<T, T> | undefined {
+    return this.namedIndexes.get(name);
+  }
+
+  /**
+   * The names of the indexes registered with
+   * {@link Collection.registerNamedIndex}, in the order they were
+   * registered.
+   * 
+   * @group Queries
+   */
+  indexNames(): string[] {
+    return Array.from(this.namedIndexes.keys());
+  }
+
+  /**
+   * A counter that increases with every mutation.
+   * 
+   * Record it along with ids or items found by a query; if it changed by the
+   * time they are used, the query might no longer return the same result.
+   * 
+   * @example
+   * ```typescript
+   * const version = collection.version;
+   * const stale = ixStatus.eq("stale");
+   * // ...
+   * if (collection.version !== version) {
+   *   throw new Error("Collection changed since the query");
+   * }
+   * collection.deleteMany(stale.map((item) => item.id));
+   * ```
+   * 
+   * @group Queries
+   */
+  get version(): number {
+    return this._version;
+  }
+
   /**
    * @group Queries
    */
@@ -67,17 +235,68 @@ export class Collection<T> {
    */
   add(value: T): Id {
     const id = this.newId();
+    value = this.runInsertHooks(id, value, undefined);
 
-    this.store.set(id, value);
-    this.propagateUpdate({
-      type: UpdateType.ADD,
-      id,
-      value,
-    });
+    this.propagateUpdate(
+      {
+        type: UpdateType.ADD,
+        id,
+        value,
+      },
+      () => this.store.set(id, value)
+    );
 
     return id;
   }
 
+  /**
+   * Adds all the given values, in order.
+   * 
+   * Complexity: O(m) where m is the number of values
+   * 
+   * @returns The {@link Id}s of the added values, in the same order.
+   * @group Mutations
+   */
+  addAll(values: Iterable<T>): Id[] {
+    const ret: Id[] = [];
+    for (const value of values) {
+      ret.push(this.add(value));
+    }
+    return ret;
+  }
+
+  /**
+   * Reserves `n` fresh {@link Id}s, in increasing order, that
+   * {@link Collection.add} will never return. Use them with
+   * {@link Collection.insertAt}, for example to have a leader process assign
+   * the ids and replicas apply the same inserts with identical ids.
+   * 
+   * Complexity: O(n)
+   * @group Mutations
+   */
+  allocateIds(n: number): Id[] {
+    const ret: Id[] = [];
+    for (let i = 0; i < n; i++) {
+      ret.push(this.newId());
+    }
+    return ret;
+  }
+
+  /**
+   * Adds a value with the given {@link Id}, like {@link Collection.set}, but
+   * refuses to replace an existing item.
+   * 
+   * Complexity: O(1)
+   * @throws Error if an item with the id already exists.
+   * @group Mutations
+   */
+  insertAt(id: Id, value: T): void {
+    if (this.store.get(id) !== undefined) {
+      throw new Error(`composable-indexes: An item with id ${id} already exists`);
+    }
+    this.set(id, value);
+  }
+
   /**
    * Complexity: O(1)
    * 
@@ -87,19 +306,107 @@ export class Collection<T> {
    */
   delete(id: Id): T | undefined {
     const oldValue = this.store.get(id);
+    this._prepareDelete(id)();
+    return oldValue;
+  }
 
-    if (oldValue === undefined) {
-      return undefined;
+  /**
+   * Deletes all the given items, letting the indexes process the deletions
+   * in one batch.
+   * 
+   * Complexity: O(m) where m is the number of ids
+   * 
+   * @returns The deleted values. Ids that don't exist are skipped.
+   * @group Mutations
+   */
+  deleteMany(ids: Iterable<Id>): T[] {
+    const updates: DeleteUpdate<T>[] = [];
+    const seen = new IdMap<true>();
+    for (const id of ids) {
+      const oldValue = this.store.get(id);
+      if (oldValue === undefined || seen.get(id)) {
+        continue;
+      }
+
+      seen.set(id, true);
+      updates.push({
+        type: UpdateType.DELETE,
+        id,
+        oldValue,
+      });
     }
 
-    this.store.delete(id);
-    this.propagateUpdate({
-      type: UpdateType.DELETE,
-      id,
-      oldValue,
+    // Every hook runs before anything is deleted, so that a veto leaves the
+    // collection unchanged.
+    for (const update of updates) {
+      this.runRemoveHooks(update.id, update.oldValue);
+    }
+
+    this.propagateUpdates(updates, () => {
+      for (const update of updates) {
+        this.store.delete(update.id);
+      }
     });
+    return updates.map((update) => update.oldValue);
+  }
 
-    return oldValue;
+  /**
+   * Deletes all but one item from each group of duplicates, for example as
+   * found by {@link HashIndex.duplicates}.
+   * 
+   * Complexity: O(m) where m is the number of items in the groups
+   * 
+   * @param keep Which item of a group to keep: the one added first or last,
+   * or the one picked by a function.
+   * @returns The deleted items.
+   * @group Mutations
+   */
+  dedup(
+    duplicates: Item<T>[][],
+    keep: "first" | "last" | ((items: Item<T>[]) => Item<T>)
+  ): Item<T>[] {
+    const removed: Item<T>[] = [];
+    for (const items of duplicates) {
+      if (items.length === 0) {
+        continue;
+      }
+
+      let survivor: Item<T>;
+      if (keep === "first" || keep === "last") {
+        const sign = keep === "first" ? 1 : -1;
+        survivor = items.reduce((a, b) =>
+          sign * a.id.compare(b.id) <= 0 ? a : b
+        );
+      } else {
+        survivor = keep(items);
+      }
+
+      for (const item of items) {
+        if (!item.id.equals(survivor.id)) {
+          removed.push(item);
+        }
+      }
+    }
+
+    this.deleteMany(removed.map((item) => item.id));
+    return removed;
+  }
+
+  /**
+   * Deletes every item in a partition of a {@link PartitionedIndex}. The
+   * index drops the partition as a whole, and the other indexes process the
+   * deletions in one batch.
+   * 
+   * Complexity: O(m) where m is the size of the partition
+   * 
+   * @returns The deleted values.
+   * @group Mutations
+   */
+  deletePartition<Key extends string | number>(
+    ix: PartitionedIndex<T, T, Key, any>,
+    key: Key
+  ): T[] {
+    return this.deleteMany(ix.ids(key));
   }
 
   /**
@@ -109,12 +416,24 @@ export class Collection<T> {
    * @group Mutations
    */
   set(id: Id, newValue: T): void {
+    this._prepareSet(id, newValue)();
+  }
+
+  /**
+   * Runs the hooks and lets the indexes validate {@link Collection.set},
+   * returning a function that carries it out. Lets an index of another
+   * collection write to this one in its commit hook, after validating the
+   * write along with its own update.
+   * 
+   * @internal
+   */
+  _prepareSet(id: Id, newValue: T): () => void {
     if(id.asLong.gt(this.last.asLong)) {
       this.last = id
     }
 
     const oldValue = this.store.get(id);
-    this.store.set(id, newValue);
+    newValue = this.runInsertHooks(id, newValue, oldValue);
 
     const update: Update<T> =
       oldValue === undefined
@@ -130,7 +449,29 @@ export class Collection<T> {
             newValue,
           };
 
-    this.propagateUpdate(update);
+    return this.prepareUpdate(update, () => this.store.set(id, newValue));
+  }
+
+  /**
+   * Like {@link Collection._prepareSet}, for {@link Collection.delete}.
+   * 
+   * @internal
+   */
+  _prepareDelete(id: Id): () => void {
+    const oldValue = this.store.get(id);
+    if (oldValue === undefined) {
+      return () => {};
+    }
+
+    this.runRemoveHooks(id, oldValue);
+    return this.prepareUpdate(
+      {
+        type: UpdateType.DELETE,
+        id,
+        oldValue,
+      },
+      () => this.store.delete(id)
+    );
   }
 
   /**
@@ -147,14 +488,13 @@ export class Collection<T> {
    */
   alter<Ret>(id: Id, f: (pre: T | undefined) => [T | undefined, Ret]): Ret {
     const pre = this.get(id);
-    if(pre) {
-      this.delete(id)
-    }
 
     const [post, ret] = f(pre);
-    if(post) {  
+    if(post !== undefined) {
       this.set(id, post)
       // TODO: Set function does another existence check, which is unnecessary
+    } else if(pre !== undefined) {
+      this.delete(id)
     }
     
     return ret
@@ -164,10 +504,233 @@ export class Collection<T> {
    * Updates a value in the collection, if it exists. 
    * 
    * Complexity: O(1)
+   * @returns The old and the new value, or `undefined` if the item doesn't
+   * exist.
+   * @group Mutations
+   */
+  adjust(id: Id, f: (pre: T) => T): Adjusted<T> | undefined {
+    return this.alter<Adjusted<T> | undefined>(id, (pre) => {
+      if (pre === undefined) return [undefined, undefined];
+      const newValue = f(pre);
+      return [newValue, { id, oldValue: pre, newValue }];
+    });
+  }
+
+  /**
+   * Updates all the given items that exist in the collection.
+   * 
+   * Complexity: O(m) where m is the number of ids
+   * @returns The old and the new values of the updated items, for example
+   * for audit logging.
+   * @group Mutations
+   */
+  adjustMany(ids: Iterable<Id>, f: (pre: T, id: Id) => T): Adjusted<T>[] {
+    const ret: Adjusted<T>[] = [];
+    for (const id of ids) {
+      const adjusted = this.adjust(id, (pre) => f(pre, id));
+      if (adjusted) {
+        ret.push(adjusted);
+      }
+    }
+    return ret;
+  }
+
+  /**
+   * Replaces all the items with the given values, for periodic full
+   * refreshes from an upstream source. The new values get fresh {@link Id}s.
+   * 
+   * Watchers are notified once, after the swap, so they never see the
+   * collection empty or half-filled.
+   * 
+   * The remove hooks of the old items and the insert hooks of the new values
+   * all run before anything changes, so a hook throwing leaves the
+   * collection unchanged. If an index rejects a new value (e.g. with a
+   * {@link ConflictException}), the indexes are rolled back to the old
+   * items, without running the hooks again, and the error is rethrown.
+   * 
+   * Complexity: O(n + m) where m is the number of values
+   * 
+   * @returns The old items.
+   * @group Mutations
+   */
+  replaceAll(values: Iterable<T>): [Id, T][] {
+    const old = this.toList();
+    for (const [id, oldValue] of old) {
+      this.runRemoveHooks(id, oldValue);
+    }
+    const added: [Id, T][] = [];
+    for (const value of values) {
+      const id = this.newId();
+      added.push([id, this.runInsertHooks(id, value, undefined)]);
+    }
+
+    this.deferWatchers++;
+    try {
+      this.deleteAllRaw(old);
+      const committed: [Id, T][] = [];
+      try {
+        for (const [id, value] of added) {
+          this.propagateUpdate({ type: UpdateType.ADD, id, value }, () =>
+            this.store.set(id, value)
+          );
+          committed.push([id, value]);
+        }
+      } catch (e) {
+        this.deleteAllRaw(committed);
+        for (const [id, value] of old) {
+          this.propagateUpdate({ type: UpdateType.ADD, id, value }, () =>
+            this.store.set(id, value)
+          );
+        }
+        throw e;
+      }
+    } finally {
+      this.deferWatchers--;
+    }
+
+    if (this.deferWatchers === 0) {
+      this.notifyWatchers();
+    }
+    return old;
+  }
+
+  // Deletes the items without running the remove hooks, which the caller
+  // already ran.
+  private deleteAllRaw(items: [Id, T][]): void {
+    const updates: DeleteUpdate<T>[] = items.map(([id, oldValue]) => ({
+      type: UpdateType.DELETE,
+      id,
+      oldValue,
+    }));
+    this.propagateUpdates(updates, () => {
+      for (const [id] of items) {
+        this.store.delete(id);
+      }
+    });
+  }
+
+  /**
+   * Moves the items matching `predicate` into a new collection, keeping their
+   * {@link Id}s. Indexes of this collection process the removals in one
+   * batch, as in {@link Collection.deleteMany}; the new collection starts
+   * without any indexes, and with the options this collection was created
+   * with.
+   * 
+   * Complexity: O(n)
    * @group Mutations
    */
-  adjust(id: Id, f: (pre: T) => T): void {
-    this.alter(id, (pre) => [pre ? f(pre) : undefined, undefined])
+  splitOff(predicate: (value: T, id: Id) => boolean): Collection<T> {
+    const moved = this.toList().filter(([id, value]) => predicate(value, id));
+    this.deleteMany(moved.map(([id]) => id));
+
+    const ret = new Collection<T>(this.options);
+    for (const [id, value] of moved) {
+      ret.set(id, value);
+    }
+    return ret;
+  }
+
+  /**
+   * Copies the items into two new collections, depending on whether they
+   * match `predicate`, keeping their {@link Id}s and the options this
+   * collection was created with. This collection is left unchanged.
+   * 
+   * Complexity: O(n)
+   * @returns The matching and the non-matching items, in that order.
+   * @group Queries
+   */
+  partition(
+    predicate: (value: T, id: Id) => boolean
+  ): [Collection<T>, Collection<T>] {
+    const matching = new Collection<T>(this.options);
+    const rest = new Collection<T>(this.options);
+    this.forEach((value, id) => {
+      (predicate(value, id) ? matching : rest).set(id, value);
+    });
+    return [matching, rest];
+  }
+
+  /**
+   * Creates a new collection by transforming every item, keeping their
+   * {@link Id}s and the options this collection was created with. Useful for
+   * migrating a collection to a new schema, after which the indexes can be
+   * registered to the new collection.
+   * 
+   * Complexity: O(n)
+   * @group Queries
+   */
+  map<U>(f: (value: T, id: Id) => U): Collection<U> {
+    const ret = new Collection<U>(this.options);
+    this.forEach((value, id) => {
+      ret.set(id, f(value, id));
+    });
+    return ret;
+  }
+
+  /**
+   * Compares the values of two collections, ignoring their {@link Id}s and
+   * indexes. A value occurring more than once has to occur the same number
+   * of times in both.
+   * 
+   * Complexity: O(n + m)
+   * 
+   * @param identity Maps a value to what it is compared by, using
+   * `SameValueZero` like a `Map` key. Defaults to the value itself, which
+   * compares objects by reference.
+   * @returns The values only in this collection, and the values only in
+   * `other`.
+   * @group Queries
+   */
+  symmetricDifference(
+    other: ReadonlyCollection<T>,
+    identity: (value: T) => unknown = (value) => value
+  ): { onlyInThis: T[]; onlyInOther: T[] } {
+    // Values of this collection not yet matched with one in `other`.
+    const unmatched: Map<unknown, T[]> = new Map();
+    this.forEach((value) => {
+      const key = identity(value);
+      const values = unmatched.get(key);
+      if (values) {
+        values.push(value);
+      } else {
+        unmatched.set(key, [value]);
+      }
+    });
+
+    const onlyInOther: T[] = [];
+    other.forEach((value) => {
+      const key = identity(value);
+      const values = unmatched.get(key);
+      if (values && values.length > 0) {
+        values.pop();
+      } else {
+        onlyInOther.push(value);
+      }
+    });
+
+    const onlyInThis: T[] = [];
+    for (const values of unmatched.values()) {
+      onlyInThis.push(...values);
+    }
+    return { onlyInThis, onlyInOther };
+  }
+
+  /**
+   * Whether both collections have the same values, see
+   * {@link Collection.symmetricDifference}.
+   * 
+   * Complexity: O(n + m)
+   * @group Queries
+   */
+  contentEquals(
+    other: ReadonlyCollection<T>,
+    identity?: (value: T) => unknown
+  ): boolean {
+    const { onlyInThis, onlyInOther } = this.symmetricDifference(
+      other,
+      identity
+    );
+    return onlyInThis.length === 0 && onlyInOther.length === 0;
   }
 
   /**
@@ -188,25 +751,346 @@ export class Collection<T> {
     return ret
   }
 
+  /**
+   * Describes the registered indexes, and the indexes they are composed of,
+   * in the order they were registered. Useful to check how a composition of
+   * `premap`s, `group`s and `zip`s ended up.
+   * 
+   * @example
+   * ```typescript
+   * console.log(JSON.stringify(collection.describeIndexes(), null, 2));
+   * ```
+   * 
+   * @group Queries
+   */
+  describeIndexes(): IndexDescription[] {
+    return this.indexes.map((ix) => ix.describe());
+  }
+
+  /**
+   * Summarizes the collection and its indexes in one value, for capacity
+   * dashboards.
+   * 
+   * Complexity: O(g + d) where g is the total number of groups in the
+   * grouped indexes, and d is the cost of {@link Index.describe} on the other
+   * indexes, which is linear in the distinct values for most of them
+   * @group Queries
+   */
+  stats(): CollectionStats {
+    const names = new Map<Index<T, T>, string>();
+    for (const [name, ix] of this.namedIndexes) {
+      names.set(ix, name);
+    }
+
+    const items = this.store.size;
+    return {
+      items,
+      indexes: this.indexes.map((ix) => {
+        const stats: IndexStats = {
+          name: names.get(ix),
+          kind: ix.constructor.name,
+        };
+
+        // Describing a grouped index registers a throwaway inner index, so
+        // the groups are counted directly instead.
+        if (ix instanceof GroupedIndex) {
+          stats.groupSizes = new Map();
+          for (const [group] of ix.groups()) {
+            const size = ix.size(group);
+            stats.groupSizes.set(size, (stats.groupSizes.get(size) ?? 0) + 1);
+          }
+          return stats;
+        }
+
+        // Look through wrappers like `premap` for the distinct values.
+        let d = ix.describe();
+        while (d.parameters.distinctValues === undefined && d.children.length === 1) {
+          d = d.children[0];
+        }
+        if (typeof d.parameters.distinctValues === "number") {
+          stats.distinctValues = d.parameters.distinctValues;
+          stats.itemsPerValue =
+            stats.distinctValues === 0 ? 0 : items / stats.distinctValues;
+        }
+        return stats;
+      }),
+    };
+  }
+
+  /**
+   * Subscribes to the result of a query.
+   * 
+   * `query` is evaluated immediately and after every mutation, and `cb` is
+   * called with the initial result and then whenever the result changes
+   * according to `equals`.
+   * 
+   * @example
+   * ```typescript
+   * const ixCount = collection.registerIndex(countIndex());
+   * const unsubscribe = collection.watch(
+   *   () => ixCount.value(),
+   *   (count) => console.log(`There are ${count} items`)
+   * );
+   * ```
+   * 
+   * Complexity: Adds the cost of `query` to every mutation.
+   * 
+   * @param equals Used to decide whether the result has changed. Defaults to
+   * `Object.is`, so queries returning fresh arrays or objects should provide
+   * a structural comparison.
+   * @returns A function that cancels the subscription.
+   * @group Queries
+   */
+  watch<R>(
+    query: () => R,
+    cb: (value: R) => void,
+    equals: (a: R, b: R) => boolean = Object.is
+  ): () => void {
+    let last = query();
+    cb(last);
+
+    const watcher = () => {
+      const next = query();
+      if (!equals(last, next)) {
+        last = next;
+        cb(next);
+      }
+    };
+
+    this.watchers.add(watcher);
+    return () => {
+      this.watchers.delete(watcher);
+    };
+  }
+
+  /**
+   * Registers a hook that runs inside every mutation that adds or replaces an
+   * item, before the indexes are updated.
+   * 
+   * The hook receives the new value, and the value being replaced if any;
+   * and returns the value to store, which allows stamping audit fields. It
+   * can throw to cancel the mutation, leaving the collection unchanged. Hooks
+   * run in the order they are registered.
+   * 
+   * Unlike {@link Collection.watch}, hooks run for every change, and can
+   * write to other collections, for example to maintain a denormalized copy.
+   * 
+   * @example
+   * ```typescript
+   * collection.onInsert((post, id, pre) => ({
+   *   ...post,
+   *   createdAt: pre?.createdAt ?? Date.now(),
+   *   updatedAt: Date.now(),
+   * }));
+   * ```
+   * 
+   * @returns A function that unregisters the hook.
+   * @group Mutations
+   */
+  onInsert(hook: InsertHook<T>): () => void {
+    this.insertHooks.add(hook);
+    return () => {
+      this.insertHooks.delete(hook);
+    };
+  }
+
+  /**
+   * Registers a hook that runs inside every mutation that deletes an item,
+   * before the indexes are updated. It can throw to cancel the mutation,
+   * leaving the collection unchanged.
+   * 
+   * @returns A function that unregisters the hook.
+   * @group Mutations
+   */
+  onRemove(hook: RemoveHook<T>): () => void {
+    this.removeHooks.add(hook);
+    return () => {
+      this.removeHooks.delete(hook);
+    };
+  }
+
+  private runInsertHooks(id: Id, value: T, oldValue: T | undefined): T {
+    for (const hook of this.insertHooks) {
+      value = hook(value, id, oldValue);
+    }
+    return value;
+  }
+
+  private runRemoveHooks(id: Id, oldValue: T): void {
+    for (const hook of this.removeHooks) {
+      hook(oldValue, id);
+    }
+  }
+
+  /**
+   * Returns a view of the collection that only exposes the queries, to hand
+   * to code that should not mutate it. Unlike using the
+   * {@link ReadonlyCollection} type directly, the view can't be cast back to
+   * a `Collection`.
+   * 
+   * Complexity: O(1)
+   * @group Queries
+   */
+  asReadonly(): ReadonlyCollection<T> {
+    const self = this;
+    return {
+      get version() {
+        return self.version;
+      },
+      get: (id) => this.get(id),
+      forEach: (f) => this.forEach(f),
+      toList: () => this.toList(),
+      partition: (predicate) => this.partition(predicate),
+      map: <U>(f: (value: T, id: Id) => U) => this.map(f),
+      symmetricDifference: (other, identity) =>
+        this.symmetricDifference(other, identity),
+      contentEquals: (other, identity) => this.contentEquals(other, identity),
+      watch: <R>(
+        query: () => R,
+        cb: (value: R) => void,
+        equals?: (a: R, b: R) => boolean
+      ) => this.watch(query, cb, equals),
+    };
+  }
+
   private newId(): Id {
     this.last = Id.fromLong(this.last.asLong.add(Long.UONE));
     return this.last;
   }
 
-  private propagateUpdate(update: Update<T>): void {
-    const commitHooks = [];
+  /**
+   * Lets every index validate the update before `apply` changes the store,
+   * so that an index throwing (e.g. a {@link ConflictException}) leaves the
+   * collection unchanged.
+   */
+  private propagateUpdate(update: Update<T>, apply: () => void): void {
+    this.prepareUpdate(update, apply)();
+  }
+
+  private prepareUpdate(update: Update<T>, apply: () => void): () => void {
+    const commitHooks: (() => void)[] = [];
     for (const index of this.indexes) {
       const hook = index._onUpdate(update);
       commitHooks.push(hook);
     }
+    return () => {
+      apply();
+      this.commit(commitHooks);
+    };
+  }
+
+  private propagateUpdates(updates: Update<T>[], apply: () => void): void {
+    if (updates.length === 0) {
+      return;
+    }
+
+    const commitHooks = [];
+    for (const index of this.indexes) {
+      const hook = index._onUpdates(updates);
+      commitHooks.push(hook);
+    }
+    apply();
+    this.commit(commitHooks);
+  }
+
+  private commit(commitHooks: (() => void)[]): void {
     for (const hook of commitHooks) {
       hook();
     }
+    this._version++;
+    if (this.deferWatchers === 0) {
+      this.notifyWatchers();
+    }
+  }
+
+  private notifyWatchers(): void {
+    for (const watcher of this.watchers) {
+      watcher();
+    }
   }
 }
 
 // Utils
 
+/**
+ * The query methods of a {@link Collection}. Use it in signatures for code
+ * that should be able to read a collection, but not change it.
+ * 
+ * @see {@link Collection.asReadonly}
+ */
+export interface ReadonlyCollection<T> {
+  readonly version: number;
+  get(id: Id): T | undefined;
+  forEach(f: (value: T, id: Id) => void): void;
+  toList(): [Id, T][];
+  partition(
+    predicate: (value: T, id: Id) => boolean
+  ): [Collection<T>, Collection<T>];
+  map<U>(f: (value: T, id: Id) => U): Collection<U>;
+  symmetricDifference(
+    other: ReadonlyCollection<T>,
+    identity?: (value: T) => unknown
+  ): { onlyInThis: T[]; onlyInOther: T[] };
+  contentEquals(
+    other: ReadonlyCollection<T>,
+    identity?: (value: T) => unknown
+  ): boolean;
+  watch<R>(
+    query: () => R,
+    cb: (value: R) => void,
+    equals?: (a: R, b: R) => boolean
+  ): () => void;
+}
+
+/**
+ * See {@link Collection.onInsert}.
+ */
+export type InsertHook<T> = (value: T, id: Id, oldValue: T | undefined) => T;
+
+/**
+ * See {@link Collection.onRemove}.
+ */
+export type RemoveHook<T> = (oldValue: T, id: Id) => void;
+
+/**
+ * See {@link Collection.stats}.
+ */
+export type CollectionStats = {
+  /** The number of items. */
+  items: number;
+  /** One entry per registered index, in the order they were registered. */
+  indexes: IndexStats[];
+};
+
+/**
+ * See {@link Collection.stats}.
+ */
+export type IndexStats = {
+  /** The name given to {@link Collection.registerNamedIndex}, if any. */
+  name?: string;
+  /** The class of the index, as in {@link Index.describe}. */
+  kind: string;
+  /** The number of distinct values, for indexes that report it. */
+  distinctValues?: number;
+  /**
+   * The number of items per distinct value, assuming every item is indexed.
+   */
+  itemsPerValue?: number;
+  /**
+   * For grouped indexes, how many groups have each number of items.
+   */
+  groupSizes?: Map<number, number>;
+};
+
+/**
+ * The result of updating an existing item.
+ */
+export type Adjusted<T> = {
+  readonly id: Id;
+  readonly oldValue: T;
+  readonly newValue: T;
+};
+
 export class ConflictException<Out, Ix extends Index<any, Out>> extends Error {
   existingValue: Out
 
@@ -220,4 +1104,10 @@ export class ConditionFailedException<Ix extends Index<any, any>> extends Error
   constructor(readonly message: string, readonly index: Ix) {
     super(`composable-indexes: Precondition failed: ${message}`);
   }
-}
\ No newline at end of file
+}
+
+export class InconsistentIndexException<Ix extends Index<any, any>> extends Error {
+  constructor(readonly message: string, readonly index: Ix) {
+    super(`composable-indexes: Inconsistent index state: ${message}`);
+  }
+}
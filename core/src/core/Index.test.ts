@@ -1,8 +1,13 @@
 import test from "node:test";
-import { PremapIndex, GroupedIndex, group, premap } from "./Index";
+import { PremapIndex, GroupedIndex, bucketed, computed, group, premap } from "./Index";
 import { HashIndex, hashIndex } from "../indexes/HashIndex";
 import fc from "fast-check";
 import { propIndexAgainstReference } from "../test_util/reference";
+import { arbCalls, playCalls } from "../test_util/call";
+import { countIndex } from "../indexes/FoldIndex";
+import { AggregateIndex } from "../indexes/AggregateIndex";
+import { Collection } from "./Collection";
+import { strict as assert } from "node:assert";
 
 type Foo = {
   bar: number;
@@ -32,6 +37,177 @@ test("Index", async () => {
     });
   });
 
+  await test("GroupedIndex.ref.sizes", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        GroupedIndex<number, number, number, HashIndex<number, number>>,
+        [boolean, number]
+      >({
+        valueGen: fc.nat({ max: 30 }),
+        index: group((i) => i % 5, hashIndex()),
+        value: (ix) => [ix.has(2), ix.size(2)],
+        reference: (arr) => {
+          const size = arr.filter((i) => i.value % 5 === 2).length;
+          return [size > 0, size];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("GroupedIndex.rankBy", () => {
+    fc.assert(
+      fc.property(
+        arbCalls({ value: fc.nat({ max: 30 }), idRange: 20, maxLength: 50 }),
+        (calls) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(group((i) => i % 5, countIndex()));
+          const ranking = ix.rankBy((inner) => inner.value());
+          playCalls(c, calls);
+
+          const expected = ix.groupsSortedBy((inner) => inner.value());
+          assert.deepEqual(ranking.ascending(), expected);
+          assert.deepEqual(ranking.top(5), [...expected].reverse());
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("GroupedIndex.groupsSortedBy.ties", () => {
+    const c = Collection.from([0, 1, 2, 5, 7, 3]);
+    const ix = c.registerIndex(group((i) => i % 5, countIndex()));
+    const sorted = (descending: boolean) =>
+      ix
+        .groupsSortedBy((inner) => inner.value(), { descending })
+        .map(([group]) => group);
+
+    // Groups 0 and 2 have two items each, 1 and 3 have one each
+    assert.deepEqual(sorted(false), [1, 3, 0, 2]);
+    assert.deepEqual(sorted(true), [0, 2, 1, 3]);
+  });
+
+  await test("GroupedIndex.foldGroups", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        GroupedIndex<number, number, number, AggregateIndex<any, number>>,
+        [number, number | undefined]
+      >({
+        valueGen: fc.nat({ max: 30 }),
+        index: group((i) => i % 5, countIndex()),
+        value: (ix) => [
+          ix.foldGroups(0, (acc, inner) => acc + inner.value()),
+          ix.reduceGroups((inner) => inner.value(), Math.max),
+        ],
+        reference: (arr) => {
+          const counts = new Map<number, number>();
+          for (const { value } of arr) {
+            counts.set(value % 5, (counts.get(value % 5) ?? 0) + 1);
+          }
+          return [
+            arr.length,
+            counts.size === 0 ? undefined : Math.max(...counts.values()),
+          ];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("GroupedIndex.at", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        GroupedIndex<
+          number,
+          number,
+          number,
+          GroupedIndex<number, number, number, AggregateIndex<any, number>>
+        >,
+        number
+      >({
+        valueGen: fc.nat({ max: 30 }),
+        index: group((i) => i % 2, group((i) => i % 3, countIndex())),
+        value: (ix) => ix.at(0).at(1).value(),
+        reference: (arr) =>
+          arr.filter((i) => i.value % 2 === 0 && i.value % 3 === 1).length,
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("bucketed", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        GroupedIndex<number, number, number, HashIndex<number, number>>,
+        [number, number][]
+      >({
+        valueGen: fc.integer({ min: -300, max: 300 }),
+        index: bucketed(100, hashIndex()),
+        value: (ix) =>
+          Array.from(ix.groups(), ([b]): [number, number] => [b, ix.size(b)]).sort(
+            (a, b) => a[0] - b[0]
+          ),
+        reference: (arr) => {
+          const counts = new Map<number, number>();
+          for (const { value } of arr) {
+            const b = Math.floor(value / 100) * 100;
+            counts.set(b, (counts.get(b) ?? 0) + 1);
+          }
+          return Array.from(counts).sort((a, b) => a[0] - b[0]);
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("bucketed.function", () => {
+    const tier = (v: number) => (v < 10 ? 0 : v < 100 ? 10 : 100);
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        GroupedIndex<number, number, number, HashIndex<number, number>>,
+        [number, number][]
+      >({
+        valueGen: fc.integer({ min: 0, max: 300 }),
+        index: bucketed(tier, hashIndex()),
+        value: (ix) =>
+          Array.from(ix.groups(), ([b]): [number, number] => [b, ix.size(b)]).sort(
+            (a, b) => a[0] - b[0]
+          ),
+        reference: (arr) => {
+          const counts = new Map<number, number>();
+          for (const { value } of arr) {
+            counts.set(tier(value), (counts.get(tier(value)) ?? 0) + 1);
+          }
+          return Array.from(counts).sort((a, b) => a[0] - b[0]);
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("bucketed.invalid width", () => {
+    assert.throws(() => bucketed(0, hashIndex()), /composable-indexes:/);
+    assert.throws(() => bucketed(-10, hashIndex()), /composable-indexes:/);
+    assert.throws(() => bucketed(NaN, hashIndex()), /composable-indexes:/);
+  });
+
   await test("PremapIndex", async () => {
     await test("ref", () => {
       fc.assert(
@@ -53,5 +229,24 @@ test("Index", async () => {
         }
       );
     });
+
+    await test("computed", () => {
+      const c = new Collection<Foo>();
+      const ix = c.registerIndex(
+        computed("total", (i: Foo) => i.bar + i.baz, hashIndex())
+      );
+      c.add({ bar: 1, baz: 2 });
+
+      assert.strictEqual(ix.name, "total");
+      assert.strictEqual(ix.get.eq(3).length, 1);
+      const [description] = c.describeIndexes();
+      assert.strictEqual(description.parameters.name, "total");
+      assert.match(String(description.parameters.definition), /i\.bar \+ i\.baz/);
+      assert.deepEqual(
+        c.registerIndex(premap((i: Foo) => i.bar, hashIndex())).describe()
+          .parameters,
+        {}
+      );
+    });
   });
 });
@@ -1,4 +1,6 @@
+import BTree from "sorted-btree";
 import { unreachable } from "../util";
+import { InconsistentIndexException } from "./Collection";
 import { AddUpdate, DeleteUpdate, Update, UpdateType, UpdateUpdate, filterMapUpdate } from "./Update";
 import { Id, Item } from "./simple_types";
 
@@ -13,15 +15,67 @@ export abstract class Index<In, Out> {
   /** @internal */
   abstract _onUpdate(update: Update<In>): () => void;
 
+  /**
+   * @internal
+   * Handles several updates, each for a different item, at once. Indexes can
+   * override this to share the work between the updates.
+   */
+  _onUpdates(updates: Update<In>[]): () => void {
+    const hooks = updates.map((update) => this._onUpdate(update));
+    return () => {
+      for (const hook of hooks) {
+        hook();
+      }
+    };
+  }
+
+  /**
+   * Describes the index and the indexes it is composed of, for diagnostics.
+   * Composite indexes override this to include their children.
+   * 
+   * @see {@link Collection.describeIndexes}
+   */
+  describe(): IndexDescription {
+    return { name: this.constructor.name, parameters: {}, children: [] };
+  }
+
   protected item(id: Id): Item<Out> {
     return new Item(id, this._indexContext.get(id)!);
   }
+
+  /**
+   * Reports that the state of the index does not agree with an update it
+   * received, for example when deleting an item it does not have.
+   * 
+   * Depending on the collection, this either throws an
+   * {@link InconsistentIndexException}, or returns so that the index can skip
+   * the update.
+   */
+  protected inconsistent(message: string): void {
+    this._indexContext.reportInconsistency(
+      new InconsistentIndexException(message, this)
+    );
+  }
 }
 
+/**
+ * The shape of an index, as returned by {@link Index.describe}.
+ */
+export type IndexDescription = {
+  name: string;
+  parameters: Record<string, unknown>;
+  children: IndexDescription[];
+};
+
 // UnregisteredIndex
 
 export class IndexContext<Out> {
-  constructor(readonly get: (_: Id) => Out | undefined) {}
+  constructor(
+    readonly get: (_: Id) => Out | undefined,
+    readonly reportInconsistency: (e: InconsistentIndexException<any>) => void = (e) => {
+      throw e;
+    }
+  ) {}
 }
 
 export class UnregisteredIndex<In, Out, Ix extends Index<In, Out>> {
@@ -96,6 +150,74 @@ export function group<In, Out, Group extends string | number, Inner extends Inde
     return GroupedIndex.create(f, inner);
 }
 
+/**
+ * Groups numbers into buckets of the given `width`, like `[0, 100)`,
+ * `[100, 200)` and so on, each keyed by its lower bound. A shorthand for
+ * {@link group}, meant for facets like price ranges: {@link GroupedIndex.size}
+ * gives the number of items in a bucket, and {@link GroupedIndex.groups}
+ * iterates over the non-empty buckets.
+ *
+ * For buckets of varying widths, pass a function from a number to the key
+ * of its bucket instead of a width.
+ * 
+ * @example
+ * ```typescript
+ * const ixPriceRanges = collection.registerIndex(
+ *   premap((p) => p.price, bucketed(100, countIndex()))
+ * );
+ * 
+ * console.log(ixPriceRanges.get.size(200)) // items priced in [200, 300)
+ *
+ * const ixPriceTiers = collection.registerIndex(
+ *   premap((p) => p.price, bucketed((v) => v < 10 ? 0 : v < 100 ? 10 : 100, countIndex()))
+ * );
+ * ```
+ */
+export function bucketed<Out, Inner extends Index<number, Out>>(
+    width: number,
+    inner: UnregisteredIndex<number, Out, Inner>
+): UnregisteredIndex<number, Out, GroupedIndex<number, Out, number, Inner>>
+export function bucketed<Out, Inner extends Index<number, Out>>(
+    bucket: (_: number) => number,
+    inner: UnregisteredIndex<number, Out, Inner>
+): UnregisteredIndex<number, Out, GroupedIndex<number, Out, number, Inner>>
+export function bucketed<Out, Inner extends Index<number, Out>>(
+    bucket: number | ((_: number) => number),
+    inner: UnregisteredIndex<number, Out, Inner>
+): UnregisteredIndex<number, Out, GroupedIndex<number, Out, number, Inner>> {
+    if (typeof bucket === "function") {
+        return group(bucket, inner)
+    }
+    const width = bucket
+    if (!(width > 0)) {
+        throw new Error("composable-indexes: Bucket width must be positive")
+    }
+    return group((v) => Math.floor(v / width) * width, inner)
+}
+
+/**
+ * Like {@link premap}, but names the computed value. The name and the source
+ * of `f` show up in {@link Index.describe}, so that indexes over derived
+ * values can be told apart in {@link Collection.describeIndexes}.
+ * 
+ * @example
+ * ```typescript
+ * const ixBmi = collection.registerIndex(
+ *   computed("bmi", (p) => p.weightKg / (p.heightM * p.heightM), btreeIndex())
+ * );
+ * 
+ * console.log(ixBmi.name); // "bmi"
+ * console.log(ixBmi.get.range({ minValue: 25 }));
+ * ```
+ */
+export function computed<In, Out, InnerIn, Inner extends Index<InnerIn, Out>>(
+    name: string,
+    f: (_: In) => InnerIn | undefined,
+    inner: UnregisteredIndex<InnerIn, Out, Inner>
+): UnregisteredIndex<In, Out, PremapIndex<In, Out, InnerIn, Inner>> {
+    return PremapIndex.create(f, inner, name);
+}
+
 // Premap functionality
 
 export class PremapIndex<
@@ -107,17 +229,20 @@ export class PremapIndex<
   private constructor(
     ctx: IndexContext<Out>,
     private inner: Inner,
-    private readonly f: (_: In) => InnerIn | undefined
+    private readonly f: (_: In) => InnerIn | undefined,
+    /** The name given with {@link computed}, if any. */
+    readonly name?: string
   ) {
     super(ctx);
   }
 
   static create<In, Out, InnerIn, Inner extends Index<InnerIn, Out>>(
     f: (_: In) => InnerIn | undefined,
-    inner: UnregisteredIndex<InnerIn, Out, Inner>
+    inner: UnregisteredIndex<InnerIn, Out, Inner>,
+    name?: string
   ): UnregisteredIndex<In, Out, PremapIndex<In, Out, InnerIn, Inner>> {
     return new UnregisteredIndex((ctx: IndexContext<Out>) => {
-      const ix = new PremapIndex(ctx, inner._register(ctx), f);
+      const ix = new PremapIndex(ctx, inner._register(ctx), f, name);
       return ix;
     });
   }
@@ -131,6 +256,33 @@ export class PremapIndex<
     }
   }
 
+  /** @internal */
+  override _onUpdates(updates: Update<In>[]): () => void {
+    const innerUpdates: Update<InnerIn>[] = [];
+    for (const update of updates) {
+      const innerUpdate = filterMapUpdate(this.f, update);
+      if (innerUpdate) {
+        innerUpdates.push(innerUpdate);
+      }
+    }
+    return this.inner._onUpdates(innerUpdates);
+  }
+
+  /**
+   * Includes the name and the source of the function, for {@link computed}
+   * indexes.
+   */
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters:
+        this.name === undefined
+          ? {}
+          : { name: this.name, definition: this.f.toString() },
+      children: [this.inner.describe()],
+    };
+  }
+
   get get(): Inner {
     return this.inner
   }
@@ -143,6 +295,8 @@ export class GroupedIndex<In, Out, Group extends string | number, Inner extends
   Out
 > {
   private readonly ixs: Map<string | number, Inner> = new Map();
+  private readonly sizes: Map<string | number, number> = new Map();
+  private readonly rankings: GroupRanking<Group, Inner, any>[] = [];
 
   private constructor(
     private readonly ctx: IndexContext<Out>,
@@ -174,59 +328,362 @@ export class GroupedIndex<In, Out, Group extends string | number, Inner extends
     }
   }
 
-  private getOrCreateGroup(group: Group): Inner {
-    let ix = this.ixs.get(group);
-    if (!ix) {
-      ix = this.inner._register(this.ctx);
-      this.ixs.set(group, ix);
+  /** @internal */
+  override _onUpdates(updates: Update<In>[]): () => void {
+    if (!updates.every((u): u is DeleteUpdate<In> => u.type === UpdateType.DELETE)) {
+      return super._onUpdates(updates);
     }
-    return ix
+
+    // Deletions are handed to each group's index in a single batch.
+    const byGroup: Map<Group, DeleteUpdate<In>[]> = new Map();
+    for (const update of updates) {
+      const group = this.group(update.oldValue);
+      const batch = byGroup.get(group);
+      if (batch) {
+        batch.push(update);
+      } else {
+        byGroup.set(group, [update]);
+      }
+    }
+
+    const hooks: (() => void)[] = [];
+    for (const [group, batch] of byGroup) {
+      const ix = this.ixs.get(group);
+      if (!ix) {
+        this.inconsistent(`Deleted items are not in group ${group}`);
+        continue;
+      }
+      const hook = ix._onUpdates(batch);
+      hooks.push(() => {
+        hook();
+        this.leave(group, batch.length);
+      });
+    }
+
+    return this.touching(Array.from(byGroup.keys()), () => {
+      for (const hook of hooks) {
+        hook();
+      }
+    });
   }
-  
+
   private add(update: AddUpdate<In>): () => void {
     const group = this.group(update.value);
-    const ix = this.getOrCreateGroup(group);
-    // TODO: If the inner index throws a ConflictException, we should delete the
-    // empty index.
-    return ix._onUpdate(update);
+    // A new group is only stored once the inner index accepts the item, so
+    // that a rejected item does not leave an empty group behind.
+    const ix = this.ixs.get(group) ?? this.inner._register(this.ctx);
+    const hook = ix._onUpdate(update);
+    return this.touching([group], () => {
+      hook();
+      this.ixs.set(group, ix);
+      this.sizes.set(group, (this.sizes.get(group) ?? 0) + 1);
+    });
   }
 
   private update(update: UpdateUpdate<In>): () => void {
     const oldGroup = this.group(update.oldValue);
     const newGroup = this.group(update.newValue);
-    if (oldGroup === newGroup) {
-      const ix = this.ixs.get(oldGroup)!;
-      return ix._onUpdate(update);
-    } else {
-      const oldIx = this.ixs.get(oldGroup)!;
-      const newIx = this.getOrCreateGroup(newGroup);
+
+    if (oldGroup !== newGroup) {
+      const deleteHook = this.delete({
+        id: update.id,
+        type: UpdateType.DELETE,
+        oldValue: update.oldValue,
+      });
+      const addHook = this.add({
+        id: update.id,
+        type: UpdateType.ADD,
+        value: update.newValue,
+      });
       return () => {
-        oldIx._onUpdate({
-          id: update.id,
-          type: UpdateType.DELETE,
-          oldValue: update.oldValue,
-        })();
-        newIx._onUpdate({
-          id: update.id,
-          type: UpdateType.ADD,
-          value: update.newValue,
-        })();
+        deleteHook();
+        addHook();
       };
     }
+
+    const ix = this.ixs.get(oldGroup);
+    if (!ix) {
+      this.inconsistent(`Updated item ${update.id} is not in group ${oldGroup}`);
+      return this.add({
+        id: update.id,
+        type: UpdateType.ADD,
+        value: update.newValue,
+      });
+    }
+    return this.touching([oldGroup], ix._onUpdate(update));
   }
 
   private delete(update: DeleteUpdate<In>): () => void {
     const group = this.group(update.oldValue);
-    const ix = this.ixs.get(group)!;
-    return ix._onUpdate(update);
-    // TODO: When an index becomes empty, we can delete it.
+    const ix = this.ixs.get(group);
+    if (!ix) {
+      this.inconsistent(`Deleted item ${update.id} is not in group ${group}`);
+      return () => {};
+    }
+    const hook = ix._onUpdate(update);
+    return this.touching([group], () => {
+      hook();
+      this.leave(group, 1);
+    });
+  }
+
+  // Drops the group once its last item leaves.
+  private leave(group: Group, count: number): void {
+    const size = (this.sizes.get(group) ?? 0) - count;
+    if (size > 0) {
+      this.sizes.set(group, size);
+    } else {
+      this.sizes.delete(group);
+      this.ixs.delete(group);
+    }
   }
 
+  // Wraps a commit hook to re-rank the groups it changes.
+  private touching(groups: Group[], hook: () => void): () => void {
+    if (this.rankings.length === 0) {
+      return hook;
+    }
+    return () => {
+      hook();
+      for (const ranking of this.rankings) {
+        for (const group of groups) {
+          ranking._touch(group, this.ixs.get(group));
+        }
+      }
+    };
+  }
+
+  /**
+   * The index of the given group, or `undefined` if the group has no items.
+   * A group is dropped as soon as its last item is deleted, so an aggregate
+   * index returned here always covers at least one item.
+   */
   get<T>(group: string | number): Inner | undefined {
     return this.ixs.get(group);
   }
 
   /** Synonym for 'get' */
   where = this.get
+
+  /**
+   * Like {@link GroupedIndex.get}, but returns an empty index for a group
+   * without items, so that nested groups can be queried without checking
+   * each level:
+   *
+   * ```typescript
+   * ixByCountryAndCity.at("NZ").at("Auckland").range({ minValue: 18 });
+   * ```
+   *
+   * The empty index is not kept up to date, so query it right away rather
+   * than holding on to it.
+   */
+  at(group: Group): Inner {
+    return this.ixs.get(group) ?? this.inner._register(this.ctx);
+  }
+
+  /**
+   * The children describe a fresh inner index, since every group has the
+   * same shape.
+   */
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters: { groups: this.ixs.size },
+      children: [this.inner._register(this.ctx).describe()],
+    };
+  }
+
+  /**
+   * Whether the group has any items.
+   *
+   * Complexity: `O(1)`
+   */
+  has(group: Group): boolean {
+    return this.ixs.has(group);
+  }
+
+  /**
+   * The number of items in the group.
+   *
+   * Complexity: `O(1)`
+   */
+  size(group: Group): number {
+    return this.sizes.get(group) ?? 0;
+  }
+
+  /**
+   * Iterates over the groups and their indexes, in no particular order.
+   */
+  groups(): IterableIterator<[Group, Inner]> {
+    return this.ixs.entries() as IterableIterator<[Group, Inner]>;
+  }
+
+  /**
+   * Combines the groups' indexes into a single value, for rollups over the
+   * whole collection like the total of per-group sums.
+   *
+   * Complexity: `O(g)` where `g` is the number of groups
+   */
+  foldGroups<A>(init: A, f: (acc: A, inner: Inner, group: Group) => A): A {
+    let acc = init;
+    for (const [group, ix] of this.groups()) {
+      acc = f(acc, ix, group);
+    }
+    return acc;
+  }
+
+  /**
+   * Like {@link GroupedIndex.foldGroups}, but without an initial value:
+   * computes a value for each group and combines them with `combine`.
+   *
+   * Complexity: `O(g)` where `g` is the number of groups
+   *
+   * @returns `undefined` if there are no groups.
+   */
+  reduceGroups<A>(
+    f: (inner: Inner, group: Group) => A,
+    combine: (a: A, b: A) => A
+  ): A | undefined {
+    let acc: A | undefined = undefined;
+    let first = true;
+    for (const [group, ix] of this.groups()) {
+      const value = f(ix, group);
+      acc = first ? value : combine(acc as A, value);
+      first = false;
+    }
+    return acc;
+  }
+
+  /**
+   * Returns the groups ordered by a value computed from each group's index.
+   * Groups with equal values are ordered by the group, ascending even when
+   * `descending` is set.
+   *
+   * Complexity: `O(g * log(g))` where `g` is the number of groups. Use
+   * {@link GroupedIndex.rankBy} to keep the order up to date instead.
+   */
+  groupsSortedBy<K extends number | string>(
+    metric: (inner: Inner) => K,
+    options?: { descending?: boolean }
+  ): [Group, Inner][] {
+    const ret = Array.from(this.groups(), ([group, ix]): [K, Group, Inner] => [
+      metric(ix),
+      group,
+      ix,
+    ]);
+    const sign = options?.descending ? -1 : 1;
+    ret.sort((a, b) => sign * compareScalar(a[0], b[0]) || compareScalar(a[1], b[1]));
+    return ret.map(([, group, ix]) => [group, ix]);
+  }
+
+  /**
+   * Keeps the groups ordered by a value computed from each group's index,
+   * re-computing it for every group an update touches.
+   *
+   * @example
+   * ```typescript
+   * const ixByTeam = collection.registerIndex(group((p) => p.team, sumIndex().premap((p) => p.score)));
+   * const leaderboard = ixByTeam.rankBy((ix) => ix.value());
+   *
+   * console.log(leaderboard.top(3));
+   * ```
+   */
+  rankBy<K extends number | string>(
+    metric: (inner: Inner) => K
+  ): GroupRanking<Group, Inner, K> {
+    const ranking = new GroupRanking<Group, Inner, K>(metric);
+    for (const [group, ix] of this.groups()) {
+      ranking._touch(group, ix);
+    }
+    this.rankings.push(ranking);
+    return ranking;
+  }
+}
+
+/**
+ * The groups of a {@link GroupedIndex}, kept ordered by a value computed from
+ * each group's index.
+ *
+ * @see {@link GroupedIndex.rankBy} as a constructor.
+ */
+export class GroupRanking<Group extends string | number, Inner, K extends number | string> {
+  private readonly order = new BTree<[K, Group], Inner>(undefined, compareRank);
+  private readonly keys: Map<Group, K> = new Map();
+
+  /** @internal */
+  constructor(private readonly metric: (inner: Inner) => K) {}
+
+  /** @internal */
+  _touch(group: Group, inner: Inner | undefined): void {
+    const oldKey = this.keys.get(group);
+    if (oldKey !== undefined) {
+      this.order.delete([oldKey, group]);
+    }
+
+    if (inner === undefined) {
+      this.keys.delete(group);
+    } else {
+      const key = this.metric(inner);
+      this.keys.set(group, key);
+      this.order.set([key, group], inner);
+    }
+  }
+
+  /**
+   * The groups in ascending order.
+   *
+   * Complexity: `O(g)` where `g` is the number of groups
+   */
+  ascending(): [Group, Inner][] {
+    return Array.from(this.order.entries(), ([[, group], ix]) => [group, ix]);
+  }
+
+  /**
+   * The `n` groups with the largest values, largest first.
+   *
+   * Complexity: `O(log(g) + n)` where `g` is the number of groups
+   */
+  top(n: number): [Group, Inner][] {
+    const ret: [Group, Inner][] = [];
+    for (const [[, group], ix] of this.order.entriesReversed()) {
+      if (ret.length >= n) break;
+      ret.push([group, ix]);
+    }
+    return ret;
+  }
+
+  /**
+   * The `n` groups with the smallest values, smallest first.
+   *
+   * Complexity: `O(log(g) + n)` where `g` is the number of groups
+   */
+  bottom(n: number): [Group, Inner][] {
+    const ret: [Group, Inner][] = [];
+    for (const [[, group], ix] of this.order.entries()) {
+      if (ret.length >= n) break;
+      ret.push([group, ix]);
+    }
+    return ret;
+  }
+
+  /**
+   * The value the group is ranked by, as of the last update to it.
+   *
+   * Complexity: `O(1)`
+   */
+  keyOf(group: Group): K | undefined {
+    return this.keys.get(group);
+  }
+}
+
+function compareRank(
+  a: [number | string, number | string],
+  b: [number | string, number | string]
+): number {
+  return compareScalar(a[0], b[0]) || compareScalar(a[1], b[1]);
+}
+
+function compareScalar(a: number | string, b: number | string): number {
+  return a < b ? -1 : a > b ? 1 : 0;
 }
 
@@ -0,0 +1,33 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import Long from "long";
+import { UpdateType, changed } from "./Update";
+import { Id } from "./simple_types";
+
+type Person = { name: string; age: number };
+
+test("Update", async () => {
+  await test("changed", () => {
+    const id = Id.fromLong(Long.UONE);
+    const alice: Person = { name: "alice", age: 30 };
+    const older: Person = { name: "alice", age: 31 };
+
+    const update = {
+      type: UpdateType.UPDATE,
+      id,
+      oldValue: alice,
+      newValue: older,
+    } as const;
+    assert.strictEqual(changed(update, (p) => p.name), false);
+    assert.strictEqual(changed(update, (p) => p.age), true);
+
+    assert.strictEqual(
+      changed({ type: UpdateType.ADD, id, value: alice }, (p) => p.name),
+      true
+    );
+    assert.strictEqual(
+      changed({ type: UpdateType.DELETE, id, oldValue: alice }, (p) => p.name),
+      true
+    );
+  });
+});
@@ -28,6 +28,21 @@ export type DeleteUpdate<T> = {
 
 export type Update<T> = AddUpdate<T> | UpdateUpdate<T> | DeleteUpdate<T>;
 
+/**
+ * Whether the part of the value selected by `f` differs before and after the
+ * update, compared with `Object.is`. Additions and deletions always count as
+ * a change.
+ *
+ * Useful for indexes and hooks that only depend on some fields, to skip the
+ * updates that don't touch them.
+ */
+export function changed<T>(update: Update<T>, f: (value: T) => unknown): boolean {
+  return (
+    update.type !== UpdateType.UPDATE ||
+    !Object.is(f(update.oldValue), f(update.newValue))
+  );
+}
+
 export function mapUpdate<From, To>(
   f: (a: From) => To,
   u: Update<From>
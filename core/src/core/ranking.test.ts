@@ -0,0 +1,42 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import Long from "long";
+import { mergeRanked } from "./ranking";
+import { Id, Item } from "./simple_types";
+
+function item(n: number): Item<string> {
+  return new Item(Id.fromLong(Long.fromNumber(n, true)), `item${n}`);
+}
+
+test("mergeRanked", async () => {
+  await test("tiers", () => {
+    const exact = [item(1)];
+    const prefix = [item(2), item(1), item(3)];
+    const fuzzy = [item(4), item(3), item(2), item(1), item(1)];
+
+    const merged = mergeRanked([
+      { items: exact, weight: 100 },
+      { items: prefix, weight: 10 },
+      { items: fuzzy, weight: 1 },
+    ]);
+    assert.deepEqual(
+      merged.map((i) => i.value),
+      ["item1", "item2", "item3", "item4"]
+    );
+  });
+
+  await test("ties and limit", () => {
+    const merged = mergeRanked(
+      [
+        { items: [item(1), item(2)], weight: 1 },
+        { items: [item(3)], weight: 1 },
+      ],
+      2
+    );
+    assert.deepEqual(
+      merged.map((i) => i.value),
+      ["item1", "item3"]
+    );
+    assert.deepEqual(mergeRanked([]), []);
+  });
+});
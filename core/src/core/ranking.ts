@@ -0,0 +1,82 @@
+import { IdMap } from "../util/IdMap";
+import { Item } from "./simple_types";
+
+/**
+ * A list of query results, in order of relevance, and how much a match in it
+ * counts.
+ */
+export type RankedSource<T> = {
+  readonly items: readonly Item<T>[];
+  readonly weight: number;
+};
+
+/**
+ * Merges the results of several queries into a single ranking, returning
+ * each item once.
+ *
+ * An item scores the sum of the weights of the sources it appears in. Items
+ * with the same score are ordered by their position in the heaviest source
+ * they appear in, and then by the order of the sources.
+ *
+ * Giving each source a weight larger than the sum of the ones after it
+ * ranks all matches of a source before the matches of the next one.
+ *
+ * Complexity: `O(m * log(m))` where `m` is the total number of results
+ *
+ * @example
+ * ```typescript
+ * const results = mergeRanked([
+ *   { items: ixByName.eq(query), weight: 100 },
+ *   { items: ixByNamePrefix.eq(query.slice(0, 3)), weight: 10 },
+ *   { items: ixText.search(query), weight: 1 },
+ * ], 20);
+ * ```
+ *
+ * @param limit The maximum number of items to return.
+ */
+export function mergeRanked<T>(
+  sources: readonly RankedSource<T>[],
+  limit?: number
+): Item<T>[] {
+  const ranked: IdMap<{
+    item: Item<T>;
+    score: number;
+    // Of the heaviest source the item appears in.
+    weight: number;
+    position: number;
+    source: number;
+    // The last source the item was counted in.
+    counted: number;
+  }> = new IdMap();
+
+  sources.forEach(({ items, weight }, source) => {
+    items.forEach((item, position) => {
+      const entry = ranked.get(item.id);
+      if (!entry) {
+        ranked.set(item.id, {
+          item,
+          score: weight,
+          weight,
+          position,
+          source,
+          counted: source,
+        });
+      } else if (entry.counted !== source) {
+        entry.counted = source;
+        entry.score += weight;
+        if (weight > entry.weight) {
+          entry.weight = weight;
+          entry.position = position;
+          entry.source = source;
+        }
+      }
+    });
+  });
+
+  const entries = Array.from(ranked.entries(), ([, entry]) => entry);
+  entries.sort(
+    (a, b) =>
+      b.score - a.score || a.position - b.position || a.source - b.source
+  );
+  return entries.slice(0, limit).map((entry) => entry.item);
+}
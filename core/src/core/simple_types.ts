@@ -14,6 +14,10 @@ export class Id {
   compare(other: Id): number {
     return this.asLong.compare(other.asLong);
   }
+
+  toString(): string {
+    return this.asLong.toString();
+  }
 }
 
 export class Item<T> {
@@ -3,18 +3,46 @@
  */
 
 export {
+  Adjusted,
   Collection,
+  CollectionStats,
   ConflictException,
   ConditionFailedException,
+  InconsistentIndexException,
+  IndexStats,
+  InsertHook,
+  ReadonlyCollection,
+  RemoveHook,
 } from "./core/Collection";
 export {
+  GroupRanking,
+  GroupedIndex,
   Index,
+  IndexContext,
+  IndexDescription,
+  PremapIndex,
   UnregisteredIndex,
+  bucketed,
+  computed,
   group,
   premap,
 } from "./core/Index";
+export {
+  AddUpdate,
+  DeleteUpdate,
+  Update,
+  UpdateType,
+  UpdateUpdate,
+  changed,
+  filterMapUpdate,
+  mapUpdate,
+} from "./core/Update";
 export {
   Id,
   Item,
 } from "./core/simple_types";
+export {
+  RankedSource,
+  mergeRanked,
+} from "./core/ranking";
 export * from "./indexes";
\ No newline at end of file
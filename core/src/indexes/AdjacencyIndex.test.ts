@@ -0,0 +1,71 @@
+import test from "node:test";
+import { AdjacencyIndex, adjacencyIndex } from "./AdjacencyIndex";
+import fc from "fast-check";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { Item } from "../core/simple_types";
+
+type Edge = { from: number; to: number };
+type Ix = AdjacencyIndex<Edge, number, Edge>;
+
+const edgeGen = fc.record({
+  from: fc.integer({ min: 0, max: 3 }),
+  to: fc.integer({ min: 0, max: 3 }),
+});
+
+const ids = (items: Item<Edge>[]) =>
+  items.map((it) => it.id.asLong.toString()).sort();
+
+test("AdjacencyIndex", async () => {
+  const refs: {
+    [name: string]: {
+      value: (ix: Ix) => unknown;
+      reference: (arr: Item<Edge>[]) => unknown;
+    };
+  } = {
+    outEdges: {
+      value: (ix) => ids(ix.outEdges(1)),
+      reference: (arr) => ids(arr.filter((it) => it.value.from === 1)),
+    },
+    inEdges: {
+      value: (ix) => ids(ix.inEdges(1)),
+      reference: (arr) => ids(arr.filter((it) => it.value.to === 1)),
+    },
+    incidentEdges: {
+      value: (ix) => ids(ix.incidentEdges(1)),
+      reference: (arr) =>
+        ids(arr.filter((it) => it.value.from === 1 || it.value.to === 1)),
+    },
+    neighbors: {
+      value: (ix) => ix.neighbors(1).sort(),
+      reference: (arr) =>
+        Array.from(
+          new Set([
+            ...arr.filter((it) => it.value.from === 1).map((it) => it.value.to),
+            ...arr.filter((it) => it.value.to === 1).map((it) => it.value.from),
+          ])
+        ).sort(),
+    },
+    degree: {
+      value: (ix) => ix.degree(1),
+      reference: (arr) =>
+        arr.filter((it) => it.value.from === 1).length +
+        arr.filter((it) => it.value.to === 1).length,
+    },
+  };
+
+  for (const [name, { value, reference }] of Object.entries(refs)) {
+    await test(`ref.${name}`, () => {
+      fc.assert(
+        propIndexAgainstReference<Edge, Ix, unknown>({
+          valueGen: edgeGen,
+          index: adjacencyIndex({ from: (e) => e.from, to: (e) => e.to }),
+          value,
+          reference,
+        }),
+        {
+          numRuns: 10000,
+        }
+      );
+    });
+  }
+});
@@ -0,0 +1,203 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdMap, IdSet, unreachable } from "../util";
+
+/**
+ * Indexes items as the directed edges of a graph, using functions that
+ * extract the source and the target vertices of an edge.
+ *
+ * Memory footprint: `O(n)`
+ *
+ * @see {@link adjacencyIndex} as a constructor.
+ */
+export class AdjacencyIndex<
+  In,
+  Vertex extends number | string,
+  Out
+> extends Index<In, Out> {
+  private readonly outgoing: Map<Vertex, IdSet> = new Map();
+  private readonly incoming: Map<Vertex, IdSet> = new Map();
+  private readonly ends: IdMap<[Vertex, Vertex]> = new IdMap();
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly from: (_: In) => Vertex,
+    private readonly to: (_: In) => Vertex
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Vertex extends number | string, Out>(args: {
+    from: (_: In) => Vertex;
+    to: (_: In) => Vertex;
+  }): UnregisteredIndex<In, Out, AdjacencyIndex<In, Vertex, Out>> {
+    return new UnregisteredIndex(
+      (ctx) => new AdjacencyIndex(ctx, args.from, args.to)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.id);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, value: In): void {
+    const from = this.from(value);
+    const to = this.to(value);
+    this.ends.set(id, [from, to]);
+    insert(this.outgoing, from, id);
+    insert(this.incoming, to, id);
+  }
+
+  private delete(id: Id): void {
+    const ends = this.ends.get(id);
+    if (!ends) {
+      this.inconsistent(`Deleted item ${id} is not indexed`);
+      return;
+    }
+    const [from, to] = ends;
+    this.ends.delete(id);
+    remove(this.outgoing, from, id);
+    remove(this.incoming, to, id);
+  }
+
+  // Queries
+
+  /**
+   * Edges starting at `vertex`.
+   *
+   * Complexity: `O(m)` where `m` is the number of items fetched
+   */
+  outEdges(vertex: Vertex): Item<Out>[] {
+    return this.items(this.outgoing.get(vertex));
+  }
+
+  /**
+   * Edges ending at `vertex`.
+   *
+   * Complexity: `O(m)` where `m` is the number of items fetched
+   */
+  inEdges(vertex: Vertex): Item<Out>[] {
+    return this.items(this.incoming.get(vertex));
+  }
+
+  /**
+   * All edges starting or ending at `vertex`, each returned once. Deleting
+   * these removes the vertex from the graph.
+   *
+   * Complexity: `O(m)` where `m` is the number of items fetched
+   */
+  incidentEdges(vertex: Vertex): Item<Out>[] {
+    const ret = this.outEdges(vertex);
+    this.incoming.get(vertex)?.forEach((id) => {
+      if (!this.outgoing.get(vertex)?.has(id)) {
+        ret.push(this.item(id));
+      }
+    });
+    return ret;
+  }
+
+  /**
+   * Distinct vertices connected to `vertex` by an edge in either direction.
+   *
+   * Complexity: `O(d)` where `d` is the degree of the vertex
+   */
+  neighbors(vertex: Vertex): Vertex[] {
+    const ret = new Set<Vertex>();
+    this.outgoing.get(vertex)?.forEach((id) => ret.add(this.ends.get(id)![1]));
+    this.incoming.get(vertex)?.forEach((id) => ret.add(this.ends.get(id)![0]));
+    return Array.from(ret);
+  }
+
+  /**
+   * The number of edges starting or ending at `vertex`. Self-loops are
+   * counted twice.
+   *
+   * Complexity: `O(1)`
+   */
+  degree(vertex: Vertex): number {
+    return this.outDegree(vertex) + this.inDegree(vertex);
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  outDegree(vertex: Vertex): number {
+    return this.outgoing.get(vertex)?.size ?? 0;
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  inDegree(vertex: Vertex): number {
+    return this.incoming.get(vertex)?.size ?? 0;
+  }
+
+  // Utils
+  private items(set: IdSet | undefined): Item<Out>[] {
+    const ret: Item<Out>[] = [];
+
+    if (!set) return ret;
+    set.forEach((id) => {
+      ret.push(this.item(id));
+    });
+
+    return ret;
+  }
+}
+
+function insert<K>(map: Map<K, IdSet>, key: K, id: Id): void {
+  const set = map.get(key);
+  if (set) {
+    set.set(id);
+  } else {
+    map.set(key, IdSet.singleton(id));
+  }
+}
+
+function remove<K>(map: Map<K, IdSet>, key: K, id: Id): void {
+  const set = map.get(key);
+  set?.delete(id);
+  if (set && set.empty()) {
+    map.delete(key);
+  }
+}
+
+/**
+ * Create a new {@link AdjacencyIndex}.
+ *
+ * @example
+ * ```typescript
+ * type Follow = { follower: string; followee: string };
+ * const collection = new Collection<Readonly<Follow>>();
+ *
+ * const ixFollows = collection.registerIndex(
+ *   adjacencyIndex({ from: (f) => f.follower, to: (f) => f.followee })
+ * );
+ *
+ * console.log(ixFollows.inDegree("alice"));
+ * ```
+ */
+export function adjacencyIndex<In, Vertex extends number | string, Out>(args: {
+  from: (_: In) => Vertex;
+  to: (_: In) => Vertex;
+}): UnregisteredIndex<In, Out, AdjacencyIndex<In, Vertex, Out>> {
+  return AdjacencyIndex.create(args);
+}
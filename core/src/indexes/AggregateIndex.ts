@@ -1,15 +1,15 @@
-import { Index, IndexContext, UnregisteredIndex } from "../core/Index";
+import { Index, IndexContext, IndexDescription, UnregisteredIndex } from "../core/Index";
 import { Update, filterMapUpdate } from "../core/Update";
 
 export abstract class AggregateIndex<In, Value> extends Index<In, any> {
   abstract value(): Value;
 }
 
-export class UnregisteredAggregateIndex<In, Value> extends UnregisteredIndex<
+export class UnregisteredAggregateIndex<
   In,
-  any,
-  AggregateIndex<In, Value>
-> {
+  Value,
+  Ix extends AggregateIndex<In, Value> = AggregateIndex<In, Value>
+> extends UnregisteredIndex<In, any, Ix> {
   map<T>(f: (value: Value) => T): UnregisteredAggregateIndex<In, T> {
     return new UnregisteredMapAggregateIndex(
       (ctx) => new MapAggregateIndex(ctx, this._register(ctx), (x: In) => x, f)
@@ -47,6 +47,10 @@ class MapAggregateIndex<
     }
   }
 
+  override describe(): IndexDescription {
+    return { ...super.describe(), children: [this.inner.describe()] };
+  }
+
   override value(): NewValue {
     return this.map(this.inner.value());
   }
@@ -1,11 +1,145 @@
 import test from "node:test";
-import { BTreeIndex, btreeIndex } from "./BTreeIndex";
+import { BTreeIndex, btreeBy, btreeIndex, floatBTreeIndex, rankedBTreeIndex } from "./BTreeIndex";
+import { OptionalIndex } from "./OptionalIndex";
 import fc from "fast-check";
 import { propIndexAgainstReference } from "../test_util/reference";
 import { testProps } from "../test_util/invariants";
 import { Item } from "../core/simple_types";
+import { Collection } from "../core/Collection";
+import { strict as assert } from "node:assert";
 
 test("BTreeIndex", async (t) => {
+  await test("floatBTreeIndex", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        OptionalIndex<number, number, number, BTreeIndex<number, number>>,
+        [number[], number, number[]]
+      >({
+        valueGen: fc.oneof(
+          fc.integer({ min: -5, max: 5 }),
+          fc.constantFrom(NaN, Infinity, -Infinity)
+        ),
+        index: floatBTreeIndex(),
+        value: (ix) => [
+          ix.get.sorted().map((i) => i.value),
+          ix.noneCount(),
+          ix.get.range({ minValue: -1, maxValue: Infinity }).map((i) => i.value).sort((a, b) => a - b),
+        ],
+        reference: (arr) => {
+          const values = arr.map((i) => i.value).filter((v) => !Number.isNaN(v));
+          values.sort((a, b) => a - b);
+          return [
+            values,
+            arr.length - values.length,
+            values.filter((v) => v >= -1),
+          ];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("btreeBy", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        BTreeIndex<number, number>,
+        [number[], number[], number | undefined]
+      >({
+        valueGen: fc.integer({ min: -10, max: 10 }),
+        index: btreeBy((a, b) => b - a),
+        value: (ix) => [
+          ix.sorted().map((i) => i.value),
+          ix
+            .range({ minValue: 5, maxValue: -2, minExclusive: true })
+            .map((i) => i.value),
+          ix.min1()?.value,
+        ],
+        reference: (arr) => {
+          const values = arr.map((i) => i.value).sort((a, b) => b - a);
+          return [values, values.filter((v) => v < 5 && v >= -2), values[0]];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("rankedBTreeIndex", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        BTreeIndex<number, number>,
+        [number, number, number, number[], boolean]
+      >({
+        valueGen: fc.integer({ min: 0, max: 10 }),
+        index: rankedBTreeIndex(),
+        value: (ix) => {
+          const count = ix.rangeCount({ minValue: 3, maxValue: 7 });
+          const sample = ix.rangeSample({ minValue: 3, maxValue: 7 }, 3);
+          const ids = sample.map((i) => i.id.toString());
+          return [
+            count,
+            ix.rangeCount({ minValue: 3, maxValue: 7, minExclusive: true, maxExclusive: true }),
+            ix.rangeCount({}),
+            count <= 3 ? sample.map((i) => i.value) : [],
+            new Set(ids).size === Math.min(count, 3) &&
+              sample.every((i) => i.value >= 3 && i.value <= 7),
+          ];
+        },
+        reference: (arr) => {
+          const values = arr.map((i) => i.value);
+          const inRange = values.filter((v) => v >= 3 && v <= 7).sort((a, b) => a - b);
+          return [
+            inRange.length,
+            values.filter((v) => v > 3 && v < 7).length,
+            values.length,
+            // With at most 3 matches, the sample is all of them.
+            inRange.length <= 3 ? inRange : [],
+            true,
+          ];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("rankedBTreeIndex.rangeSample", () => {
+    const c = Collection.from(Array.from({ length: 100 }, (_, i) => i));
+    const ix = c.registerIndex(rankedBTreeIndex<number, number>());
+
+    let seed = 1;
+    const random = () => (seed = (seed * 16807) % 2147483647) / 2147483647;
+    const sample = ix.rangeSample({ minValue: 20, maxValue: 80, maxExclusive: true }, 10, random).map((i) => i.value);
+
+    assert.strictEqual(sample.length, 10);
+    assert.strictEqual(new Set(sample).size, 10);
+    assert.deepEqual(sample, [...sample].sort((a, b) => a - b));
+    assert.ok(sample.every((v) => v >= 20 && v < 80));
+    assert.throws(() => c.registerIndex(btreeIndex<number, number>()).rangeCount({}));
+  });
+
+  await test("rankedBTreeIndex.rangeSample.deterministic", () => {
+    const values = Array.from({ length: 100 }, (_, i) => i);
+    const run = (order: number[]) => {
+      const c = new Collection<number>();
+      const ix = c.registerIndex(rankedBTreeIndex<number, number>());
+      c.addAll(order);
+
+      let seed = 42;
+      const random = () => (seed = (seed * 16807) % 2147483647) / 2147483647;
+      return ix.rangeSample({ minValue: 10, maxValue: 90 }, 5, random).map((i) => i.value);
+    };
+
+    assert.deepEqual(run(values), run([...values].reverse()));
+  });
+
   await test("ref.eq", () => {
     fc.assert(
       propIndexAgainstReference<
@@ -73,6 +207,88 @@ test("BTreeIndex", async (t) => {
     );
   });
 
+  await test("ref.range.bounds", () => {
+    fc.assert(
+      fc.property(
+        fc.option(fc.integer({ min: 0, max: 5 }), { nil: undefined }),
+        fc.option(fc.integer({ min: 0, max: 5 }), { nil: undefined }),
+        fc.boolean(),
+        fc.boolean(),
+        (minValue, maxValue, minExclusive, maxExclusive) =>
+          fc.assert(
+            propIndexAgainstReference<
+              number,
+              BTreeIndex<number, number>,
+              number[]
+            >({
+              valueGen: fc.integer({ min: 0, max: 5 }),
+              index: btreeIndex(),
+              value: (ix) =>
+                ix
+                  .range({ minValue, maxValue, minExclusive, maxExclusive })
+                  .map((i) => i.value)
+                  .sort(),
+              reference: (arr) =>
+                arr
+                  .map((it) => it.value)
+                  .filter(
+                    (v) =>
+                      (minValue === undefined ||
+                        (minExclusive ? v > minValue : v >= minValue)) &&
+                      (maxValue === undefined ||
+                        (maxExclusive ? v < maxValue : v <= maxValue))
+                  )
+                  .sort(),
+            }),
+            {
+              numRuns: 100,
+            }
+          )
+      ),
+      {
+        numRuns: 50,
+      }
+    );
+  });
+
+  await test("ref.sorted", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        BTreeIndex<number, number>,
+        number[][]
+      >({
+        valueGen: fc.integer({ min: 0, max: 5 }),
+        index: btreeIndex(),
+        value: (ix) => [
+          ix.sorted().map((i) => i.value),
+          ix.sorted({ descending: true }).map((i) => i.value),
+        ],
+        reference: (arr) => {
+          const values = arr.map((it) => it.value).sort((a, b) => a - b);
+          return [values, [...values].reverse()];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("into", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(btreeIndex<number, number>());
+    c.addAll([1, 2, 2, 3]);
+
+    const buffer: Item<number>[] = [];
+    assert.strictEqual(ix.eq(2, buffer), buffer);
+    ix.range({ minValue: 3, into: buffer });
+    assert.deepEqual(
+      buffer.map((i) => i.value),
+      [2, 2, 3]
+    );
+  });
+
   const witnesses = {
     eq: (ix: BTreeIndex<number, number>) =>
       ix
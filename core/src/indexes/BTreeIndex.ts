@@ -1,28 +1,50 @@
 import { Id } from "..";
 import {
   Index,
+  IndexContext,
+  IndexDescription,
   UnregisteredIndex,
 } from "../core/Index";
-import { Update, UpdateType } from "../core/Update";
+import { DeleteUpdate, Update, UpdateType } from "../core/Update";
 import { Item } from "../core/simple_types";
-import { IdSet, unreachable } from "../util";
+import { IdSet, RankTree, unreachable } from "../util";
 import BTree from "sorted-btree";
+import { OptionalIndex, optional } from "./OptionalIndex";
 
 /** 
  * An index backed by a `BTree` (from the `sorted-btree` package).
  * 
  * Stores the elements in a sorted order, and allows efficient queries for
- * equality, range and max/min.
+ * equality, range and max/min. The values are in their natural order, unless
+ * the index is constructed with {@link btreeBy}.
  * 
  * Memory footprint: `O(n * log(n))`
  * 
  * @group indexes 
  */
 export class BTreeIndex<In extends number | string, Out> extends Index<In, Out> {
-  private readonly ix = new BTree<number | string, IdSet>();
+  private readonly ix: BTree<In, IdSet>;
+  // Only kept by the indexes constructed with `rankedBTreeIndex`, for
+  // counting and sampling ranges without visiting them.
+  private readonly ranks: RankTree<[In, Id]> | undefined;
 
-  static create<In extends number | string, Out>(): UnregisteredIndex<In, Out, BTreeIndex<In, Out>> {
-    return new UnregisteredIndex((ctx) => new BTreeIndex(ctx));
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly compare?: (a: In, b: In) => number,
+    ranked = false
+  ) {
+    super(ctx);
+    this.ix = new BTree(undefined, compare);
+    this.ranks = ranked
+      ? new RankTree((a, b) => this.compareValues(a[0], b[0]) || a[1].compare(b[1]))
+      : undefined;
+  }
+
+  static create<In extends number | string, Out>(
+    compare?: (a: In, b: In) => number,
+    ranked = false
+  ): UnregisteredIndex<In, Out, BTreeIndex<In, Out>> {
+    return new UnregisteredIndex((ctx) => new BTreeIndex(ctx, compare, ranked));
   }
 
   /** @internal */
@@ -40,6 +62,41 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
     };
   }
 
+  /** @internal */
+  override _onUpdates(updates: Update<In>[]): () => void {
+    if (!updates.every((u): u is DeleteUpdate<In> => u.type === UpdateType.DELETE)) {
+      return super._onUpdates(updates);
+    }
+
+    return () => {
+      // Look up each distinct value once, rather than once per deleted item.
+      const byValue: Map<In, Id[]> = new Map();
+      for (const update of updates) {
+        const ids = byValue.get(update.oldValue);
+        if (ids) {
+          ids.push(update.id);
+        } else {
+          byValue.set(update.oldValue, [update.id]);
+        }
+      }
+
+      for (const [value, ids] of byValue) {
+        const set = this.ix.get(value);
+        for (const id of ids) {
+          if (!set?.has(id)) {
+            this.inconsistent(`Deleted item ${id} is not indexed under ${value}`);
+            continue;
+          }
+          set.delete(id);
+          this.ranks?.delete([value, id]);
+        }
+        if (set?.empty()) {
+          this.ix.delete(value);
+        }
+      }
+    };
+  }
+
   private add(id: Id, value: In): void {
     const set = this.ix.get(value);
     if (set !== undefined) {
@@ -47,6 +104,7 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
     } else {
       this.ix.set(value, IdSet.singleton(id));
     }
+    this.ranks?.insert([value, id]);
   }
 
   private update(id: Id, oldValue: In, newValue: In): void {
@@ -59,14 +117,34 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
 
   private delete(id: Id, oldValue: In): void {
     const set = this.ix.get(oldValue);
-    set?.delete(id);
-    if (set && set.empty()) {
+    if (!set?.has(id)) {
+      this.inconsistent(`Deleted item ${id} is not indexed under ${oldValue}`);
+      return;
+    }
+    set.delete(id);
+    if (set.empty()) {
       this.ix.delete(oldValue);
     }
+    this.ranks?.delete([oldValue, id]);
   }
 
   // Queries
 
+  /**
+   * Includes the number of distinct values, and the most items sharing a
+   * value.
+   */
+  override describe(): IndexDescription {
+    let maxItemsPerValue = 0;
+    for (const [_, s] of this.ix.entries()) {
+      maxItemsPerValue = Math.max(maxItemsPerValue, s.size);
+    }
+    return {
+      ...super.describe(),
+      parameters: { distinctValues: this.ix.size, maxItemsPerValue },
+    };
+  }
+
   /** 
    * Complexity: `O(1)`
    */
@@ -76,9 +154,12 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
 
   /**
    * Complexity: `O(log(n))`
+   * 
+   * @param into An array to append the results to, instead of a new one.
+   * Reusing an array avoids an allocation per query on hot paths.
    */
-  eq(value: In): Item<Out>[] {
-    return this.items(this.ix.get(value))
+  eq(value: In, into: Item<Out>[] = []): Item<Out>[] {
+    return this.items(this.ix.get(value), into)
   }
 
   /**
@@ -131,15 +212,44 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
 
   /**
    * Returns at most `limit` items that is between `minValue` and `maxValue`
-   * (inclusive).
+   * (inclusive, unless `minExclusive` or `maxExclusive` is set). A missing
+   * bound leaves that side of the range open.
+   * 
+   * Results are appended to `into` if given, like {@link BTreeIndex.eq}.
    * 
    * Complexity: `O(log(n) + limit)`
    */
-  range(p: { minValue: In; maxValue: In; limit?: number }): Item<Out>[] {
-    const { minValue, maxValue, limit } = p;
-    const values = this.ix.getRange(minValue, maxValue, true, limit);
+  range(p: {
+    minValue?: In;
+    maxValue?: In;
+    minExclusive?: boolean;
+    maxExclusive?: boolean;
+    limit?: number;
+    into?: Item<Out>[];
+  }): Item<Out>[] {
+    const { limit } = p;
+    const ret = p.into ?? [];
+    const low = p.minValue ?? this.ix.minKey();
+    const high = p.maxValue ?? this.ix.maxKey();
+    if (low === undefined || high === undefined) return ret;
+    const minExclusive = p.minValue !== undefined && p.minExclusive;
+    const maxExclusive = p.maxValue !== undefined && p.maxExclusive;
+
+    // `getRange` always includes the lower bound, so fetch one more value in
+    // case the first one has to be skipped.
+    const values = this.ix.getRange(
+      low,
+      high,
+      !maxExclusive,
+      limit !== undefined && minExclusive ? limit + 1 : limit
+    );
+    if (minExclusive && values.length > 0 && this.equal(values[0][0], low)) {
+      values.shift();
+    }
+    if (limit !== undefined && values.length > limit) {
+      values.length = limit;
+    }
 
-    const ret: Item<Out>[] = [];
     for (const [_, s] of values) {
       for (const id of s.values()) {
         ret.push(this.item(id))
@@ -149,10 +259,112 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
     return ret;
   }
 
-  // utils
-  private items(set: IdSet | undefined): Item<Out>[] {
+  /**
+   * Returns all items, ordered by their value. Items with equal values are in
+   * no particular order.
+   * 
+   * The result is a copy, so it stays valid while the collection changes;
+   * for example while writing it out across `await`s.
+   * 
+   * Complexity: `O(n)`
+   */
+  sorted(p?: { descending?: boolean }): Item<Out>[] {
     const ret: Item<Out>[] = [];
+    const entries = p?.descending ? this.ix.entriesReversed() : this.ix.entries();
+    for (const [_, s] of entries) {
+      this.items(s, ret);
+    }
+    return ret;
+  }
+
+  /**
+   * The number of items in a range, bounded like {@link BTreeIndex.range},
+   * without visiting them. Useful for showing "12,400 results" for a huge
+   * range.
+   *
+   * Complexity: `O(log(n))`
+   *
+   * @throws Error unless the index is constructed with
+   * {@link rankedBTreeIndex}.
+   */
+  rangeCount(p: RangeBounds<In>): number {
+    const [lo, hi] = this.rankBounds(p);
+    return hi - lo;
+  }
+
+  /**
+   * Picks `n` distinct items of a range uniformly at random, in order, or
+   * all of them if there are at most `n`. Bounded like
+   * {@link BTreeIndex.range}.
+   *
+   * Complexity: `O(n * log(n))`
+   *
+   * The sample depends only on the numbers `random` returns and on the set
+   * of items in the range, not on the order they were added in, so a
+   * generator seeded the same way picks the same items from the same data,
+   * in property tests or replayed simulations alike.
+   *
+   * @param random Returns a number in `[0, 1)`. Defaults to `Math.random`;
+   * pass a seeded generator for reproducible samples.
+   * @throws Error unless the index is constructed with
+   * {@link rankedBTreeIndex}.
+   */
+  rangeSample(
+    p: RangeBounds<In>,
+    n: number,
+    random: () => number = Math.random
+  ): Item<Out>[] {
+    const [lo, hi] = this.rankBounds(p);
+    const count = hi - lo;
+
+    // Floyd's algorithm, picking `n` distinct ranks out of `count`.
+    const picked = new Set<number>();
+    for (let j = count - Math.min(n, count); j < count; j++) {
+      const t = Math.floor(random() * (j + 1));
+      picked.add(picked.has(t) ? j : t);
+    }
+
+    return Array.from(picked)
+      .sort((a, b) => a - b)
+      .map((rank) => this.item(this.ranks!.at(lo + rank)![1]));
+  }
+
+  // utils
+  private rankBounds(p: RangeBounds<In>): [number, number] {
+    const ranks = this.ranks;
+    if (!ranks) {
+      throw new Error("composable-indexes: Range counts need a rankedBTreeIndex");
+    }
+    const { minValue, maxValue } = p;
+    const lo =
+      minValue === undefined
+        ? 0
+        : ranks.countBelow(([v]) =>
+            p.minExclusive
+              ? this.compareValues(v, minValue) <= 0
+              : this.compareValues(v, minValue) < 0
+          );
+    const hi =
+      maxValue === undefined
+        ? ranks.size
+        : ranks.countBelow(([v]) =>
+            p.maxExclusive
+              ? this.compareValues(v, maxValue) < 0
+              : this.compareValues(v, maxValue) <= 0
+          );
+    return [lo, Math.max(lo, hi)];
+  }
+
+  private compareValues(a: In, b: In): number {
+    if (this.compare) return this.compare(a, b);
+    return a < b ? -1 : a > b ? 1 : 0;
+  }
+
+  private equal(a: In, b: In): boolean {
+    return this.compare ? this.compare(a, b) === 0 : a === b;
+  }
 
+  private items(set: IdSet | undefined, ret: Item<Out>[] = []): Item<Out>[] {
     if(!set) return ret;
 
     set.forEach((id) => {
@@ -165,4 +377,84 @@ export class BTreeIndex<In extends number | string, Out> extends Index<In, Out>
 
 export function btreeIndex<In  extends number | string, Out>(): UnregisteredIndex<In, Out, BTreeIndex<In, Out>> {
   return BTreeIndex.create();
-}
\ No newline at end of file
+}
+
+/**
+ * The bounds of a range query, see {@link BTreeIndex.range}.
+ */
+export type RangeBounds<In> = {
+  minValue?: In;
+  maxValue?: In;
+  minExclusive?: boolean;
+  maxExclusive?: boolean;
+};
+
+/**
+ * A {@link BTreeIndex} that also keeps the items in an order-statistic tree,
+ * to support {@link BTreeIndex.rangeCount} and
+ * {@link BTreeIndex.rangeSample}. Updates cost an extra `O(log(n))`.
+ *
+ * @example
+ * ```typescript
+ * const ixPrice = collection.registerIndex(
+ *   premap((p) => p.price, rankedBTreeIndex())
+ * );
+ *
+ * const range = { minValue: 10, maxValue: 100 };
+ * console.log(`${ixPrice.get.rangeCount(range)} results`);
+ * const preview = ixPrice.get.rangeSample(range, 20);
+ * ```
+ */
+export function rankedBTreeIndex<In extends number | string, Out>(): UnregisteredIndex<
+  In,
+  Out,
+  BTreeIndex<In, Out>
+> {
+  return BTreeIndex.create(undefined, true);
+}
+
+/**
+ * A {@link BTreeIndex} ordered by `compare` instead of the natural order of
+ * the values. All queries follow that order: with a descending comparator,
+ * `min` returns the largest values, `sorted` starts from the largest value,
+ * and `range` expects `minValue` to be the larger bound.
+ *
+ * @example
+ * ```typescript
+ * // Highest priority first
+ * const ixPriority = collection.registerIndex(
+ *   premap((t) => t.priority, btreeBy<number, Task>((a, b) => b - a))
+ * );
+ *
+ * const next = ixPriority.get.min1();
+ * const urgent = ixPriority.get.range({ minValue: 10, maxValue: 5 });
+ * ```
+ */
+export function btreeBy<In extends number | string, Out>(
+  compare: (a: In, b: In) => number
+): UnregisteredIndex<In, Out, BTreeIndex<In, Out>> {
+  return BTreeIndex.create(compare);
+}
+/**
+ * A {@link BTreeIndex} over floating-point numbers, which keeps `NaN`s aside
+ * since they are not ordered with respect to any number. Query the ordered
+ * values through `get`, and the `NaN`s through `noneItems`. `-0` and `0` are
+ * the same value.
+ *
+ * @example
+ * ```typescript
+ * const ixPrice = collection.registerIndex(
+ *   premap((p) => p.price, floatBTreeIndex())
+ * );
+ *
+ * const cheap = ixPrice.get.get.range({ maxValue: 10 });
+ * const unpriced = ixPrice.get.noneItems();
+ * ```
+ */
+export function floatBTreeIndex<Out>(): UnregisteredIndex<
+  number,
+  Out,
+  OptionalIndex<number, Out, number, BTreeIndex<number, Out>>
+> {
+  return optional((v) => (Number.isNaN(v) ? undefined : v), btreeIndex());
+}
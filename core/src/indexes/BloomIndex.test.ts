@@ -0,0 +1,108 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { bloomIndex, groupedBloomIndex } from "./BloomIndex";
+import fc from "fast-check";
+import { Collection, InconsistentIndexException } from "../core/Collection";
+import { premap } from "../core/Index";
+import { arbCalls, playCalls } from "../test_util/call";
+
+test("BloomIndex", async () => {
+  await test("no false negatives", () => {
+    fc.assert(
+      fc.property(
+        arbCalls({
+          value: fc.integer({ min: 0, max: 1000 }),
+          idRange: 10,
+          maxLength: 100,
+        }),
+        (calls) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(bloomIndex({ bits: 64, hashes: 3 }));
+          playCalls(c, calls);
+          c.forEach((v) => assert.ok(ix.mightContain(v)));
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("delete", () => {
+    const c = new Collection<string>();
+    const ix = c.registerIndex(bloomIndex({ bits: 1024, hashes: 4 }));
+
+    const id = c.add("alice");
+    assert.ok(ix.mightContain("alice"));
+
+    c.delete(id);
+    assert.ok(!ix.mightContain("alice"));
+  });
+
+  await test("delete of a value that was never added", () => {
+    const reported: InconsistentIndexException<any>[] = [];
+    const c = new Collection<string>({
+      onInconsistency: (e) => reported.push(e),
+    });
+    // A non-deterministic premap puts the index out of sync
+    let salt = "";
+    const ix = c.registerIndex(
+      premap((v: string) => v + salt, bloomIndex({ bits: 1024, hashes: 4 }))
+    );
+
+    const id = c.add("alice");
+    salt = "!";
+    c.delete(id);
+    assert.strictEqual(reported.length, 1);
+
+    // The counters did not wrap around
+    salt = "";
+    assert.ok(ix.get.mightContain("alice"));
+    assert.ok(!ix.get.mightContain("alice!"));
+  });
+
+  await test("grouped", () => {
+    type User = { tenant: string; email: string };
+    fc.assert(
+      fc.property(
+        arbCalls({
+          value: fc.record({
+            tenant: fc.constantFrom("a", "b", "c"),
+            email: fc.constantFrom("x", "y", "z"),
+          }),
+          idRange: 10,
+          maxLength: 100,
+        }),
+        (calls) => {
+          const c = new Collection<User>();
+          const ix = c.registerIndex(
+            groupedBloomIndex({
+              group: (u: User) => u.tenant,
+              value: (u: User) => u.email,
+              bits: 1024,
+              hashes: 4,
+            })
+          );
+          playCalls(c, calls);
+
+          c.forEach((u) => assert.ok(ix.mightContainIn(u.tenant, u.email)));
+          for (const tenant of ["a", "b", "c"]) {
+            if (!c.toList().some(([, u]) => u.tenant === tenant)) {
+              assert.ok(!ix.mightContainIn(tenant, "x"));
+            }
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("invalid parameters", () => {
+    assert.throws(() => bloomIndex({ bits: 0, hashes: 3 }), /composable-indexes: bits/);
+    assert.throws(() => bloomIndex({ bits: 10.5, hashes: 3 }), /composable-indexes: bits/);
+    assert.throws(() => bloomIndex({ bits: 64, hashes: 0 }), /composable-indexes: hashes/);
+    assert.throws(() => bloomIndex({ bits: 64, hashes: NaN }), /composable-indexes: hashes/);
+  });
+});
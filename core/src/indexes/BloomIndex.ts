@@ -0,0 +1,264 @@
+import {
+  GroupedIndex,
+  Index,
+  IndexContext,
+  IndexDescription,
+  PremapIndex,
+  UnregisteredIndex,
+  group,
+  premap,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Id } from "../core/simple_types";
+import { fnv1a, unreachable } from "../util";
+
+/**
+ * A counting [Bloom filter](https://en.wikipedia.org/wiki/Bloom_filter), for
+ * approximate membership queries in constant memory.
+ *
+ * {@link BloomIndex.mightContain} never returns `false` for a value in the
+ * collection, but can return `true` for a value that is not. Counters
+ * saturate at 255, after which they are never decremented; this only
+ * increases the false positive rate.
+ *
+ * See {@link GroupedBloomIndex} for memory-bounded per-group membership
+ * checks.
+ *
+ * Memory footprint: `O(bits)`
+ *
+ * @see {@link bloomIndex} as a constructor.
+ */
+export class BloomIndex<In extends number | string, Out> extends Index<
+  In,
+  Out
+> {
+  private readonly counters: Uint8Array;
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    bits: number,
+    private readonly hashes: number
+  ) {
+    super(ctx);
+    this.counters = new Uint8Array(bits);
+  }
+
+  static create<In extends number | string, Out>(args: {
+    bits: number;
+    hashes: number;
+  }): UnregisteredIndex<In, Out, BloomIndex<In, Out>> {
+    if (!(Number.isInteger(args.bits) && args.bits >= 1)) {
+      throw new Error("composable-indexes: bits must be a positive integer");
+    }
+    if (!(Number.isInteger(args.hashes) && args.hashes >= 1)) {
+      throw new Error("composable-indexes: hashes must be a positive integer");
+    }
+    return new UnregisteredIndex(
+      (ctx) => new BloomIndex(ctx, args.bits, args.hashes)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.id, update.oldValue);
+        this.add(update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id, update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(value: In): void {
+    for (const slot of this.slots(value)) {
+      if (this.counters[slot] < 255) this.counters[slot]++;
+    }
+  }
+
+  private delete(id: Id, value: In): void {
+    const slots = this.slots(value);
+    if (slots.some((slot) => this.counters[slot] === 0)) {
+      // Leaving the counters as they are can only cause false positives.
+      this.inconsistent(`Deleted item ${id} is not indexed`);
+      return;
+    }
+    for (const slot of slots) {
+      if (this.counters[slot] < 255) this.counters[slot]--;
+    }
+  }
+
+  // Double hashing, as in Kirsch & Mitzenmacher, "Less Hashing, Same
+  // Performance".
+  private slots(value: In): number[] {
+    const str = String(value);
+    const h1 = fnv1a(str);
+    const h2 = fnv1a(str, h1) | 1;
+
+    const ret: number[] = [];
+    for (let i = 0; i < this.hashes; i++) {
+      ret.push(((h1 + Math.imul(i, h2)) >>> 0) % this.counters.length);
+    }
+    return ret;
+  }
+
+  // Queries
+
+  /**
+   * Complexity: `O(hashes)`
+   */
+  mightContain(value: In): boolean {
+    return this.slots(value).every((slot) => this.counters[slot] > 0);
+  }
+}
+
+/**
+ * A {@link BloomIndex} per group, for membership checks scoped to a group like
+ * a tenant. Each group takes `O(bits)` memory, and groups are dropped once
+ * they have no items.
+ *
+ * Memory footprint: `O(g * bits)` where `g` is the number of groups
+ *
+ * @see {@link groupedBloomIndex} as a constructor.
+ */
+export class GroupedBloomIndex<
+  In,
+  Out,
+  Group extends string | number,
+  Value extends number | string
+> extends Index<In, Out> {
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly inner: GroupedIndex<
+      In,
+      Out,
+      Group,
+      PremapIndex<In, Out, Value, BloomIndex<Value, Out>>
+    >
+  ) {
+    super(ctx);
+  }
+
+  static create<
+    In,
+    Out,
+    Group extends string | number,
+    Value extends number | string
+  >(args: {
+    group: (_: In) => Group;
+    value: (_: In) => Value;
+    bits: number;
+    hashes: number;
+  }): UnregisteredIndex<In, Out, GroupedBloomIndex<In, Out, Group, Value>> {
+    const inner = group(
+      args.group,
+      premap(args.value, BloomIndex.create<Value, Out>(args))
+    );
+    return new UnregisteredIndex(
+      (ctx) => new GroupedBloomIndex(ctx, inner._register(ctx))
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return this.inner._onUpdate(update);
+  }
+
+  /** @internal */
+  override _onUpdates(updates: Update<In>[]): () => void {
+    return this.inner._onUpdates(updates);
+  }
+
+  override describe(): IndexDescription {
+    return { ...super.describe(), children: [this.inner.describe()] };
+  }
+
+  // Queries
+
+  /**
+   * Whether an item in `group` might have `value`. Like
+   * {@link BloomIndex.mightContain}, never returns `false` for a value in the
+   * group, and always returns `false` for a group without items.
+   *
+   * Complexity: `O(hashes)`
+   */
+  mightContainIn(group: Group, value: Value): boolean {
+    return this.inner.get(group)?.get.mightContain(value) ?? false;
+  }
+
+  get get(): GroupedIndex<
+    In,
+    Out,
+    Group,
+    PremapIndex<In, Out, Value, BloomIndex<Value, Out>>
+  > {
+    return this.inner;
+  }
+}
+
+/**
+ * Create a new {@link BloomIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixEmailsByTenant = collection.registerIndex(
+ *   group(
+ *     (u) => u.tenant,
+ *     premap((u) => u.email, bloomIndex({ bits: 8192, hashes: 4 }))
+ *   )
+ * );
+ *
+ * const maybeTaken =
+ *   ixEmailsByTenant.get("acme")?.get.mightContain("bob@acme.com") ?? false;
+ * ```
+ *
+ * @param args.bits The number of counters. Each counter takes a byte.
+ * @param args.hashes The number of counters set per value.
+ */
+export function bloomIndex<In extends number | string, Out>(args: {
+  bits: number;
+  hashes: number;
+}): UnregisteredIndex<In, Out, BloomIndex<In, Out>> {
+  return BloomIndex.create(args);
+}
+
+/**
+ * Create a new {@link GroupedBloomIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixEmailsByTenant = collection.registerIndex(
+ *   groupedBloomIndex({
+ *     group: (u) => u.tenant,
+ *     value: (u) => u.email,
+ *     bits: 8192,
+ *     hashes: 4,
+ *   })
+ * );
+ *
+ * const maybeTaken = ixEmailsByTenant.mightContainIn("acme", "bob@acme.com");
+ * ```
+ *
+ * @param args.group The group of an item.
+ * @param args.value The value of an item to check membership of.
+ * @param args.bits The number of counters per group. Each counter takes a
+ * byte.
+ * @param args.hashes The number of counters set per value.
+ */
+export function groupedBloomIndex<
+  In,
+  Out,
+  Group extends string | number,
+  Value extends number | string
+>(args: {
+  group: (_: In) => Group;
+  value: (_: In) => Value;
+  bits: number;
+  hashes: number;
+}): UnregisteredIndex<In, Out, GroupedBloomIndex<In, Out, Group, Value>> {
+  return GroupedBloomIndex.create(args);
+}
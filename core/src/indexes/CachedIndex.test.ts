@@ -0,0 +1,60 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { Collection } from "../core/Collection";
+import { arbCalls, playCalls } from "../test_util/call";
+import { cached } from "./CachedIndex";
+import { hashIndex } from "./HashIndex";
+
+test("CachedIndex", async () => {
+  await test("agrees with the inner index", () => {
+    fc.assert(
+      fc.property(
+        fc.array(
+          fc.tuple(
+            arbCalls({ value: fc.nat({ max: 5 }), idRange: 10, maxLength: 5 }),
+            fc.nat({ max: 5 })
+          ),
+          { maxLength: 20 }
+        ),
+        (steps) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(cached((v: number) => v, hashIndex<number, number>(), 3));
+          const eq = (inner: typeof ix.get, key: number) =>
+            inner.eq(key).map((i) => i.id.toString()).sort();
+
+          for (const [calls, key] of steps) {
+            playCalls(c, calls);
+            assert.deepEqual(ix.query(key, eq), eq(ix.get, key));
+            assert.ok(ix.size() <= 3);
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("memoizes", () => {
+    const c = Collection.from([1, 2]);
+    const ix = c.registerIndex(cached((v: number) => v, hashIndex<number, number>(), 10));
+    let calls = 0;
+    const count = (inner: typeof ix.get, key: number) => {
+      calls++;
+      return inner.eq(key).length;
+    };
+
+    assert.strictEqual(ix.query(1, count), 1);
+    assert.strictEqual(ix.query(1, count), 1);
+    assert.strictEqual(calls, 1);
+
+    c.add(2);
+    assert.strictEqual(ix.query(1, count), 1);
+    assert.strictEqual(calls, 1);
+
+    c.add(1);
+    assert.strictEqual(ix.query(1, count), 2);
+    assert.strictEqual(calls, 2);
+  });
+});
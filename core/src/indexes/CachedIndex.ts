@@ -0,0 +1,134 @@
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+
+/**
+ * Wraps an index, memoizing the results of queries keyed by a value, like
+ * equality lookups. An update only invalidates the results for the keys of
+ * the values it touches, so queries must only depend on the items with the
+ * given key.
+ *
+ * At most `capacity` results are kept; the least recently used one is
+ * evicted first.
+ *
+ * Memory footprint: `O(capacity)` on top of the inner index
+ *
+ * @see {@link cached} as a constructor.
+ */
+export class CachedIndex<
+  In,
+  Out,
+  Key,
+  Inner extends Index<In, Out>
+> extends Index<In, Out> {
+  private readonly cache: Map<Key, unknown> = new Map();
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly inner: Inner,
+    private readonly key: (_: In) => Key,
+    private readonly capacity: number
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Out, Key, Inner extends Index<In, Out>>(
+    key: (_: In) => Key,
+    inner: UnregisteredIndex<In, Out, Inner>,
+    capacity: number
+  ): UnregisteredIndex<In, Out, CachedIndex<In, Out, Key, Inner>> {
+    return new UnregisteredIndex(
+      (ctx) => new CachedIndex(ctx, inner._register(ctx), key, capacity)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    const hook = this.inner._onUpdate(update);
+    return () => {
+      hook();
+      if (update.type === UpdateType.ADD) {
+        this.cache.delete(this.key(update.value));
+      } else if (update.type === UpdateType.UPDATE) {
+        this.cache.delete(this.key(update.oldValue));
+        this.cache.delete(this.key(update.newValue));
+      } else if (update.type === UpdateType.DELETE) {
+        this.cache.delete(this.key(update.oldValue));
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  /**
+   * Returns the memoized result for `key`, or runs `f` on the inner index
+   * and memoizes it. The same `f` should be used for every query, since
+   * results are only keyed by `key`.
+   *
+   * Complexity: `O(1)` on a hit
+   */
+  query<R>(key: Key, f: (inner: Inner, key: Key) => R): R {
+    if (this.cache.has(key)) {
+      const hit = this.cache.get(key) as R;
+      // Move it to the back, as the most recently used.
+      this.cache.delete(key);
+      this.cache.set(key, hit);
+      return hit;
+    }
+
+    const result = f(this.inner, key);
+    this.cache.set(key, result);
+    if (this.cache.size > this.capacity) {
+      this.cache.delete(this.cache.keys().next().value as Key);
+    }
+    return result;
+  }
+
+  /**
+   * The number of memoized results.
+   */
+  size(): number {
+    return this.cache.size;
+  }
+
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters: { capacity: this.capacity, cached: this.cache.size },
+      children: [this.inner.describe()],
+    };
+  }
+
+  /**
+   * The inner index, for queries that should not be memoized.
+   */
+  get get(): Inner {
+    return this.inner;
+  }
+}
+
+/**
+ * Wraps an index in a {@link CachedIndex}. `key` gives the query argument an
+ * item affects, so that updates to it invalidate the right results.
+ *
+ * @example
+ * ```typescript
+ * const ixByCountry = collection.registerIndex(
+ *   cached((u) => u.country, premap((u) => u.country, hashIndex()), 100)
+ * );
+ *
+ * const nz = ixByCountry.query("NZ", (ix, country) => ix.get.eq(country));
+ * ```
+ */
+export function cached<In, Out, Key, Inner extends Index<In, Out>>(
+  key: (_: In) => Key,
+  inner: UnregisteredIndex<In, Out, Inner>,
+  capacity: number
+): UnregisteredIndex<In, Out, CachedIndex<In, Out, Key, Inner>> {
+  return CachedIndex.create(key, inner, capacity);
+}
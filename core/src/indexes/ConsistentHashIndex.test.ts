@@ -0,0 +1,58 @@
+import test from "node:test";
+import fc from "fast-check";
+import { strict as assert } from "node:assert";
+import {
+  ConsistentHashIndex,
+  consistentHashIndex,
+} from "./ConsistentHashIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { fnv1a } from "../util";
+
+const KEYS = ["a", "b", "user:1", "user:2", "order:17", "x"];
+
+test("ConsistentHashIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        string,
+        ConsistentHashIndex<string, string>,
+        [(string | undefined)[], boolean]
+      >({
+        valueGen: fc.constantFrom("n1", "n2", "n3", "n4", "n5"),
+        index: consistentHashIndex(8),
+        value: (ix) => {
+          const total = ix.shares().reduce((acc, [, share]) => acc + share, 0);
+          return [
+            KEYS.map((k) => ix.nodeFor(k)?.value),
+            ix.shares().length === 0 || Math.abs(total - 1) < 1e-9,
+          ];
+        },
+        reference: (arr) => {
+          const points: [number, string][] = [];
+          for (const { value } of arr) {
+            for (let i = 0; i < 8; i++) {
+              points.push([fnv1a(`${value}#${i}`), value]);
+            }
+          }
+          points.sort((a, b) => a[0] - b[0]);
+          return [
+            KEYS.map((k) => {
+              const hash = fnv1a(k);
+              return (points.find(([p]) => p >= hash) ?? points[0])?.[1];
+            }),
+            true,
+          ];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("invalid replicas", () => {
+    for (const replicas of [0, -1, 1.5, NaN]) {
+      assert.throws(() => consistentHashIndex(replicas), /composable-indexes: replicas/);
+    }
+  });
+});
@@ -0,0 +1,161 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdMap, fnv1a, unreachable } from "../util";
+import BTree from "sorted-btree";
+
+const RING_SIZE = 2 ** 32;
+
+/**
+ * Places the items on a [consistent hash
+ * ring](https://en.wikipedia.org/wiki/Consistent_hashing), to route keys to
+ * them; for example when the items are the nodes of a sharded service. Each
+ * item is placed at `replicas` points derived from its indexed value, so
+ * adding or removing an item only moves the keys next to its points.
+ *
+ * Memory footprint: `O(n * replicas)`
+ *
+ * @see {@link consistentHashIndex} as a constructor.
+ */
+export class ConsistentHashIndex<In extends number | string, Out> extends Index<
+  In,
+  Out
+> {
+  // Points with the same hash are ordered by the time they were added, and
+  // the first one owns the arc.
+  private readonly ring = new BTree<number, Id[]>();
+  private readonly points: IdMap<number[]> = new IdMap();
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly replicas: number
+  ) {
+    super(ctx);
+  }
+
+  static create<In extends number | string, Out>(
+    replicas: number
+  ): UnregisteredIndex<In, Out, ConsistentHashIndex<In, Out>> {
+    if (!(Number.isInteger(replicas) && replicas >= 1)) {
+      throw new Error("composable-indexes: replicas must be a positive integer");
+    }
+    return new UnregisteredIndex(
+      (ctx) => new ConsistentHashIndex(ctx, replicas)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        if (update.oldValue === update.newValue) return;
+        this.delete(update.id);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, value: In): void {
+    const points: number[] = [];
+    for (let i = 0; i < this.replicas; i++) {
+      const point = fnv1a(`${value}#${i}`);
+      points.push(point);
+      const ids = this.ring.get(point);
+      if (ids) {
+        ids.push(id);
+      } else {
+        this.ring.set(point, [id]);
+      }
+    }
+    this.points.set(id, points);
+  }
+
+  private delete(id: Id): void {
+    const points = this.points.get(id);
+    if (!points) {
+      this.inconsistent(`Deleted item ${id} is not indexed`);
+      return;
+    }
+    this.points.delete(id);
+    for (const point of points) {
+      const ids = this.ring.get(point)!;
+      const i = ids.findIndex((other) => other.equals(id));
+      ids.splice(i, 1);
+      if (ids.length === 0) {
+        this.ring.delete(point);
+      }
+    }
+  }
+
+  // Queries
+
+  /**
+   * The item owning `key`: the one with the first point at or after the
+   * hash of the key, wrapping around the ring.
+   *
+   * Complexity: `O(log(n * replicas))`
+   *
+   * @returns `undefined` if the collection is empty.
+   */
+  nodeFor(key: string | number): Item<Out> | undefined {
+    const hash = fnv1a(String(key));
+    const pair =
+      this.ring.nextHigherPair(hash - 1) ?? this.ring.nextHigherPair(undefined);
+    return pair ? this.item(pair[1][0]) : undefined;
+  }
+
+  /**
+   * The fraction of the ring owned by each item, which is the expected
+   * fraction of the keys routed to it. Useful to check how evenly the keys
+   * are spread, and to pick the number of replicas.
+   *
+   * Complexity: `O(n * replicas)`
+   */
+  shares(): [Item<Out>, number][] {
+    const owned: IdMap<number> = new IdMap();
+    let previous = this.ring.maxKey()! - RING_SIZE;
+    for (const [point, ids] of this.ring.entries()) {
+      owned.set(ids[0], (owned.get(ids[0]) ?? 0) + (point - previous) / RING_SIZE);
+      previous = point;
+    }
+
+    const ret: [Item<Out>, number][] = [];
+    owned.forEach((share, id) => {
+      ret.push([this.item(id), share]);
+    });
+    return ret;
+  }
+}
+
+/**
+ * Create a new {@link ConsistentHashIndex}.
+ *
+ * @example
+ * ```typescript
+ * const nodes = new Collection<Readonly<{ name: string; address: string }>>();
+ * const ixRing = nodes.registerIndex(
+ *   premap((n) => n.name, consistentHashIndex(100))
+ * );
+ *
+ * const owner = ixRing.get.nodeFor("user:42");
+ * ```
+ *
+ * @param replicas The number of points per item. More points spread the
+ * keys more evenly, at the cost of memory.
+ */
+export function consistentHashIndex<In extends number | string, Out>(
+  replicas: number = 100
+): UnregisteredIndex<In, Out, ConsistentHashIndex<In, Out>> {
+  return ConsistentHashIndex.create(replicas);
+}
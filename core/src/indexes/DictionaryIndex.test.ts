@@ -0,0 +1,39 @@
+import test from "node:test";
+import fc from "fast-check";
+import { DictionaryIndex, dictionaryIndex } from "./DictionaryIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+const VALUES = [0, 1, 2, 3, 4, 5];
+
+test("DictionaryIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        DictionaryIndex<number, number>,
+        [number, string[]][]
+      >({
+        valueGen: fc.constantFrom(...VALUES),
+        index: dictionaryIndex(),
+        value: (ix) =>
+          VALUES.map((v) => {
+            const code = ix.encode(v);
+            if (code === undefined) return [0, []];
+            if (ix.decode(code) !== v) throw new Error("decode(encode(v)) !== v");
+            return [
+              ix.count(code),
+              ix.eq(code).map((i) => i.id.toString()).sort(),
+            ];
+          }),
+        reference: (arr) =>
+          VALUES.map((v) => {
+            const ids = arr.filter((i) => i.value === v).map((i) => i.id.toString());
+            return [ids.length, ids.sort()];
+          }),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
@@ -0,0 +1,152 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdSet, unreachable } from "../util";
+
+/**
+ * Assigns dense numeric codes, starting from `0`, to the distinct values in
+ * the order they are first seen, and keeps the items of each code.
+ *
+ * Codes are never reused or reassigned, even when the last item with a value
+ * is deleted, so they can be stored elsewhere, for example as an Arrow
+ * dictionary column.
+ *
+ * Memory footprint: `O(n + d)` where `d` is the number of distinct values
+ * ever seen
+ *
+ * @see {@link dictionaryIndex} as a constructor.
+ */
+export class DictionaryIndex<In extends number | string, Out> extends Index<
+  In,
+  Out
+> {
+  private readonly codes: Map<In, number> = new Map();
+  private readonly values: In[] = [];
+  private readonly ids: IdSet[] = [];
+
+  private constructor(ctx: IndexContext<Out>) {
+    super(ctx);
+  }
+
+  static create<In extends number | string, Out>(): UnregisteredIndex<
+    In,
+    Out,
+    DictionaryIndex<In, Out>
+  > {
+    return new UnregisteredIndex((ctx) => new DictionaryIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        if (update.oldValue === update.newValue) return;
+        this.delete(update.id, update.oldValue);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id, update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, value: In): void {
+    let code = this.codes.get(value);
+    if (code === undefined) {
+      code = this.values.length;
+      this.codes.set(value, code);
+      this.values.push(value);
+      this.ids.push(new IdSet());
+    }
+    this.ids[code].set(id);
+  }
+
+  private delete(id: Id, value: In): void {
+    const code = this.codes.get(value);
+    if (code === undefined || !this.ids[code].has(id)) {
+      this.inconsistent(`Deleted item ${id} is not indexed under ${value}`);
+      return;
+    }
+    this.ids[code].delete(id);
+  }
+
+  // Queries
+
+  /**
+   * The code of `value`, or `undefined` if it was never seen.
+   *
+   * Complexity: `O(1)`
+   */
+  encode(value: In): number | undefined {
+    return this.codes.get(value);
+  }
+
+  /**
+   * The value with the given code, or `undefined` if there is no such code.
+   *
+   * Complexity: `O(1)`
+   */
+  decode(code: number): In | undefined {
+    return this.values[code];
+  }
+
+  /**
+   * The number of codes assigned so far. Codes are `0` to `size() - 1`.
+   *
+   * Complexity: `O(1)`
+   */
+  size(): number {
+    return this.values.length;
+  }
+
+  /**
+   * The number of items with the value of the given code.
+   *
+   * Complexity: `O(1)`
+   */
+  count(code: number): number {
+    return this.ids[code]?.size ?? 0;
+  }
+
+  /**
+   * The items with the value of the given code.
+   *
+   * Complexity: `O(m)` where `m` is the number of items fetched
+   */
+  eq(code: number): Item<Out>[] {
+    const ret: Item<Out>[] = [];
+    this.ids[code]?.forEach((id) => {
+      ret.push(this.item(id));
+    });
+    return ret;
+  }
+}
+
+/**
+ * Create a new {@link DictionaryIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixCountry = collection.registerIndex(
+ *   premap((u) => u.country, dictionaryIndex())
+ * );
+ *
+ * const code = ixCountry.get.encode("NZ");
+ * console.log(code === undefined ? 0 : ixCountry.get.count(code));
+ * ```
+ */
+export function dictionaryIndex<In extends number | string, Out>(): UnregisteredIndex<
+  In,
+  Out,
+  DictionaryIndex<In, Out>
+> {
+  return DictionaryIndex.create();
+}
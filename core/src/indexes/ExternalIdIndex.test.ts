@@ -0,0 +1,37 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { externalIdIndex } from "./ExternalIdIndex";
+import { Collection, ConflictException } from "../core/Collection";
+import { premap } from "../core/Index";
+
+type User = { uuid: string; name: string };
+
+test("ExternalIdIndex", async () => {
+  await test("mapping", () => {
+    const c = new Collection<User>();
+    const ix = c.registerIndex(
+      premap((u: User) => u.uuid, externalIdIndex<string, User>())
+    );
+
+    const alice = c.add({ uuid: "a-1", name: "alice" });
+    assert.strictEqual(ix.get.idOf("a-1"), alice);
+    assert.strictEqual(ix.get.externalOf(alice), "a-1");
+
+    assert.throws(
+      () => c.add({ uuid: "a-1", name: "mallory" }),
+      ConflictException
+    );
+
+    // Updating other fields keeps the mapping.
+    c.set(alice, { uuid: "a-1", name: "Alice" });
+    assert.strictEqual(ix.get.idOf("a-1"), alice);
+
+    c.set(alice, { uuid: "a-2", name: "Alice" });
+    assert.strictEqual(ix.get.idOf("a-1"), undefined);
+    assert.strictEqual(ix.get.externalOf(alice), "a-2");
+
+    c.delete(alice);
+    assert.strictEqual(ix.get.idOf("a-2"), undefined);
+    assert.strictEqual(ix.get.externalOf(alice), undefined);
+  });
+});
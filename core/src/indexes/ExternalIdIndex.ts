@@ -0,0 +1,118 @@
+import { ConflictException, Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { IdMap, unreachable } from "../util";
+
+/**
+ * A two-way mapping between the {@link Id}s of the items and identifiers
+ * from outside the collection, like UUIDs or database keys.
+ *
+ * Throws a {@link ConflictException} if two items would have the same
+ * external identifier.
+ *
+ * Memory footprint: `O(n)`
+ *
+ * @see {@link externalIdIndex} as a constructor.
+ */
+export class ExternalIdIndex<In extends number | string, Out> extends Index<
+  In,
+  Out
+> {
+  private readonly ids: Map<In, Id> = new Map();
+  private readonly externals: IdMap<In> = new IdMap();
+
+  private constructor(ctx: IndexContext<Out>) {
+    super(ctx);
+  }
+
+  static create<In extends number | string, Out>(): UnregisteredIndex<
+    In,
+    Out,
+    ExternalIdIndex<In, Out>
+  > {
+    return new UnregisteredIndex((ctx) => new ExternalIdIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    if (update.type === UpdateType.ADD) {
+      this.checkFree(update.id, update.value);
+    } else if (update.type === UpdateType.UPDATE) {
+      this.checkFree(update.id, update.newValue);
+    }
+
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.id);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private checkFree(id: Id, external: In): void {
+    const existing = this.ids.get(external);
+    if (existing !== undefined && !existing.equals(id)) {
+      throw new ConflictException(existing, this);
+    }
+  }
+
+  private add(id: Id, external: In): void {
+    this.ids.set(external, id);
+    this.externals.set(id, external);
+  }
+
+  private delete(id: Id): void {
+    const external = this.externals.get(id);
+    if (external === undefined) {
+      this.inconsistent(`Deleted item ${id} has no external id`);
+      return;
+    }
+    this.ids.delete(external);
+    this.externals.delete(id);
+  }
+
+  // Queries
+
+  /**
+   * Complexity: `O(1)`
+   */
+  idOf(external: In): Id | undefined {
+    return this.ids.get(external);
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  externalOf(id: Id): In | undefined {
+    return this.externals.get(id);
+  }
+}
+
+/**
+ * Create a new {@link ExternalIdIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixUuids = collection.registerIndex(
+ *   premap((u) => u.uuid, externalIdIndex())
+ * );
+ *
+ * const id = ixUuids.get.idOf(request.params.uuid);
+ * ```
+ */
+export function externalIdIndex<
+  In extends number | string,
+  Out
+>(): UnregisteredIndex<In, Out, ExternalIdIndex<In, Out>> {
+  return ExternalIdIndex.create();
+}
@@ -0,0 +1,50 @@
+import test from "node:test";
+import fc from "fast-check";
+import { FlagIndex, flagIndex } from "./FlagIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { testProps } from "../test_util/invariants";
+
+test("FlagIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        boolean,
+        FlagIndex<boolean>,
+        [number, number, boolean, boolean, string[], number | undefined]
+      >({
+        valueGen: fc.boolean(),
+        index: flagIndex(),
+        value: (ix) => [
+          ix.countTrue(),
+          ix.countFalse(),
+          ix.all(),
+          ix.any(),
+          ix.trueItems().map((i) => i.id.toString()).sort(),
+          ix.ratio(),
+        ],
+        reference: (arr) => {
+          const trues = arr.filter((i) => i.value);
+          return [
+            trues.length,
+            arr.length - trues.length,
+            trues.length === arr.length,
+            trues.length > 0,
+            trues.map((i) => i.id.toString()).sort(),
+            arr.length === 0 ? undefined : trues.length / arr.length,
+          ];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("props", async (t) => {
+    await testProps<boolean, FlagIndex<boolean>, [number, number]>(t, {
+      valueGen: fc.boolean(),
+      index: flagIndex(),
+      witness: (ix) => [ix.countTrue(), ix.countFalse()],
+    });
+  });
+});
@@ -0,0 +1,147 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdSet, unreachable } from "../util";
+
+/**
+ * Splits the items by a boolean, keeping the count and the items of each
+ * side.
+ *
+ * Memory footprint: `O(n)`
+ *
+ * @see {@link flagIndex} as a constructor.
+ */
+export class FlagIndex<Out> extends Index<boolean, Out> {
+  private readonly trues = new IdSet();
+  private readonly falses = new IdSet();
+
+  private constructor(ctx: IndexContext<Out>) {
+    super(ctx);
+  }
+
+  static create<Out>(): UnregisteredIndex<boolean, Out, FlagIndex<Out>> {
+    return new UnregisteredIndex((ctx) => new FlagIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<boolean>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        if (update.oldValue === update.newValue) return;
+        this.delete(update.id, update.oldValue);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id, update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, flag: boolean): void {
+    (flag ? this.trues : this.falses).set(id);
+  }
+
+  private delete(id: Id, flag: boolean): void {
+    const set = flag ? this.trues : this.falses;
+    if (!set.has(id)) {
+      this.inconsistent(`Deleted item ${id} is not indexed under ${flag}`);
+      return;
+    }
+    set.delete(id);
+  }
+
+  // Queries
+
+  /**
+   * Whether every item is `true`. Holds for an empty collection.
+   *
+   * Complexity: `O(1)`
+   */
+  all(): boolean {
+    return this.falses.empty();
+  }
+
+  /**
+   * Whether any item is `true`.
+   *
+   * Complexity: `O(1)`
+   */
+  any(): boolean {
+    return !this.trues.empty();
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  countTrue(): number {
+    return this.trues.size;
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  countFalse(): number {
+    return this.falses.size;
+  }
+
+  /**
+   * The fraction of the items that are `true`, or `undefined` if there are
+   * no items.
+   *
+   * Complexity: `O(1)`
+   */
+  ratio(): number | undefined {
+    const total = this.trues.size + this.falses.size;
+    return total === 0 ? undefined : this.trues.size / total;
+  }
+
+  /**
+   * Complexity: `O(m)` where `m` is the number of items fetched
+   */
+  trueItems(): Item<Out>[] {
+    return this.items(this.trues);
+  }
+
+  /**
+   * Complexity: `O(m)` where `m` is the number of items fetched
+   */
+  falseItems(): Item<Out>[] {
+    return this.items(this.falses);
+  }
+
+  // Utils
+  private items(set: IdSet): Item<Out>[] {
+    const ret: Item<Out>[] = [];
+    set.forEach((id) => {
+      ret.push(this.item(id));
+    });
+    return ret;
+  }
+}
+
+/**
+ * Create a new {@link FlagIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixDone = collection.registerIndex(premap((t) => t.done, flagIndex()));
+ *
+ * console.log(`${ixDone.get.countTrue()} tasks done`);
+ * const allDone = ixDone.get.all();
+ * ```
+ */
+export function flagIndex<Out>(): UnregisteredIndex<
+  boolean,
+  Out,
+  FlagIndex<Out>
+> {
+  return FlagIndex.create();
+}
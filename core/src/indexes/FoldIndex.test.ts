@@ -0,0 +1,166 @@
+import test from "node:test";
+import fc from "fast-check";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { AggregateIndex } from "./AggregateIndex";
+import {
+  Checked,
+  checkedMeanIndex,
+  checkedSumIndex,
+  countIfIndex,
+  rateIndex,
+  ratioIndex,
+  sumIfIndex,
+} from "./FoldIndex";
+import { Collection } from "../core/Collection";
+import { arbCalls, playCalls } from "../test_util/call";
+import { strict as assert } from "node:assert";
+
+const valueGen = fc.oneof(
+  fc.integer({ min: -100, max: 100 }),
+  fc.constantFrom(NaN, Infinity, -Infinity)
+);
+
+test("FoldIndex", async () => {
+  // Values are updated in place by the generated calls, so they move in and
+  // out of the predicate.
+  const positive = (v: number) => v > 0;
+
+  await test("sumIfIndex.ref", () => {
+    fc.assert(
+      propIndexAgainstReference<number, AggregateIndex<number, number>, number>({
+        valueGen: fc.integer({ min: -10, max: 10 }),
+        index: sumIfIndex(positive, (v) => v * 2),
+        value: (ix) => ix.value(),
+        reference: (arr) =>
+          arr
+            .map((i) => i.value)
+            .filter(positive)
+            .reduce((a, v) => a + v * 2, 0),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("countIfIndex.ref", () => {
+    fc.assert(
+      propIndexAgainstReference<number, AggregateIndex<number, number>, number>({
+        valueGen: fc.integer({ min: -10, max: 10 }),
+        index: countIfIndex(positive),
+        value: (ix) => ix.value(),
+        reference: (arr) => arr.filter((i) => positive(i.value)).length,
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("ratioIndex.ref", () => {
+    type Ratio = { matching: number; total: number; ratio: number };
+    fc.assert(
+      propIndexAgainstReference<number, AggregateIndex<number, Ratio>, Ratio>({
+        valueGen: fc.integer({ min: -10, max: 10 }),
+        index: ratioIndex(positive),
+        value: (ix) => ix.value(),
+        reference: (arr) => {
+          const matching = arr.filter((i) => positive(i.value)).length;
+          return { matching, total: arr.length, ratio: matching / arr.length };
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("rateIndex.ref", () => {
+    const halfLife = 10;
+    const lambda = Math.LN2 / halfLife;
+    const now = 5100;
+
+    fc.assert(
+      fc.property(
+        arbCalls({
+          // Clusters hundreds of half-lives apart
+          value: fc.oneof(
+            fc.integer({ min: 0, max: 50 }),
+            fc.integer({ min: 2000, max: 2050 }),
+            fc.integer({ min: 5000, max: 5050 })
+          ),
+          idRange: 10,
+          maxLength: 100,
+        }),
+        (calls) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(rateIndex({ halfLife }));
+          playCalls(c, calls);
+
+          let expected = 0;
+          c.forEach((t) => {
+            expected += lambda * Math.exp(-lambda * (now - t));
+          });
+          const actual = ix.value()(now);
+          assert.ok(
+            Math.abs(actual - expected) <= 1e-9 * expected,
+            `expected ${expected}, got ${actual}`
+          );
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("rateIndex.invalid halfLife", () => {
+    for (const halfLife of [0, -10, NaN]) {
+      assert.throws(() => rateIndex({ halfLife }), /composable-indexes: halfLife/);
+    }
+  });
+
+  await test("checkedSumIndex.ref", () => {
+    fc.assert(
+      propIndexAgainstReference<number, AggregateIndex<number, Checked>, Checked>({
+        valueGen,
+        index: checkedSumIndex(),
+        value: (ix) => ix.value(),
+        reference: (arr) => {
+          const finite = arr.map((i) => i.value).filter(Number.isFinite);
+          return {
+            value: finite.reduce((a, b) => a + b, 0),
+            nonFinite: arr.length - finite.length,
+          };
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("checkedMeanIndex.poison.ref", () => {
+    fc.assert(
+      propIndexAgainstReference<number, AggregateIndex<number, Checked>, Checked>({
+        valueGen,
+        index: checkedMeanIndex("poison"),
+        value: (ix) => ix.value(),
+        reference: (arr) => {
+          const values = arr.map((i) => i.value);
+          const nonFinite = values.filter((v) => !Number.isFinite(v)).length;
+          return {
+            value:
+              nonFinite > 0
+                ? NaN
+                : values.reduce((a, b) => a + b, 0) / values.length,
+            nonFinite,
+          };
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
@@ -138,6 +138,67 @@ export function arithmeticMeanIndex(): UnregisteredAggregateIndex<
   }).premap((a) => ({ sum: a, count: 1 }));
 }
 
+/**
+ * What {@link checkedSumIndex} and {@link checkedMeanIndex} do with `NaN`
+ * and infinite inputs: leave them out of the result, or make the result
+ * `NaN` for as long as any of them are in the collection.
+ */
+export type NonFinitePolicy = "skip" | "poison";
+
+/**
+ * The result of {@link checkedSumIndex} and {@link checkedMeanIndex}, along
+ * with the number of `NaN` or infinite inputs currently in the collection.
+ */
+export type Checked = { value: number; nonFinite: number };
+
+function checkedIndex(
+  policy: NonFinitePolicy,
+  result: (sum: number, count: number) => number
+): UnregisteredAggregateIndex<number, Checked> {
+  return algebraicGroupIndex({
+    empty: { sum: 0, count: 0, nonFinite: 0 },
+    append: (a, b) => ({
+      sum: a.sum + b.sum,
+      count: a.count + b.count,
+      nonFinite: a.nonFinite + b.nonFinite,
+    }),
+    inverse: (a) => ({ sum: -a.sum, count: -a.count, nonFinite: -a.nonFinite }),
+    result: (a) => ({
+      value: policy === "poison" && a.nonFinite > 0 ? NaN : result(a.sum, a.count),
+      nonFinite: a.nonFinite,
+    }),
+  }).premap((a: number) =>
+    Number.isFinite(a)
+      ? { sum: a, count: 1, nonFinite: 0 }
+      : { sum: 0, count: 0, nonFinite: 1 }
+  );
+}
+
+/**
+ * Like {@link sumIndex}, but guards against `NaN` and infinite inputs. A
+ * single one of those would otherwise make the sum `NaN` even after it is
+ * deleted, since `Infinity - Infinity` is `NaN`.
+ * 
+ * `O(1)` query, update, and memory complexity.
+ */
+export function checkedSumIndex(
+  policy: NonFinitePolicy = "skip"
+): UnregisteredAggregateIndex<number, Checked> {
+  return checkedIndex(policy, (sum) => sum);
+}
+
+/**
+ * Like {@link arithmeticMeanIndex}, but guards against `NaN` and infinite
+ * inputs. With the `"skip"` policy, the mean is over the finite values only.
+ * 
+ * `O(1)` query, update, and memory complexity.
+ */
+export function checkedMeanIndex(
+  policy: NonFinitePolicy = "skip"
+): UnregisteredAggregateIndex<number, Checked> {
+  return checkedIndex(policy, (sum, count) => sum / count);
+}
+
 /**
  * An aggregate index for calculating the number of values.
  * 
@@ -151,4 +212,144 @@ export function countIndex(): UnregisteredAggregateIndex<any, number> {
     delete: (st) => st - 1,
     result: (st) => st,
   })
-}
\ No newline at end of file
+}
+
+/**
+ * An aggregate index for calculating the sum of `f` over the values matching
+ * `predicate`.
+ * 
+ * `O(1)` query, update, and memory complexity.
+ */
+export function sumIfIndex<In>(
+  predicate: (value: In) => boolean,
+  f: (value: In) => number
+): UnregisteredAggregateIndex<In, number> {
+  return sumIndex().premap((v: In) => (predicate(v) ? f(v) : undefined));
+}
+
+/**
+ * An aggregate index for calculating the number of values matching
+ * `predicate`.
+ * 
+ * `O(1)` query, update, and memory complexity.
+ */
+export function countIfIndex<In>(
+  predicate: (value: In) => boolean
+): UnregisteredAggregateIndex<In, number> {
+  return countIndex().premap((v: In) => (predicate(v) ? v : undefined));
+}
+
+/**
+ * An aggregate index for calculating the number of values matching
+ * `predicate`, alongside the total number of values and their ratio.
+ * 
+ * The ratio is `NaN` when there are no values.
+ * 
+ * `O(1)` query, update, and memory complexity.
+ */
+export function ratioIndex<In>(
+  predicate: (value: In) => boolean
+): UnregisteredAggregateIndex<
+  In,
+  { matching: number; total: number; ratio: number }
+> {
+  return algebraicGroupIndex({
+    empty: { matching: 0, total: 0 },
+    append: (a, b) => ({
+      matching: a.matching + b.matching,
+      total: a.total + b.total,
+    }),
+    inverse: (a) => ({ matching: -a.matching, total: -a.total }),
+    result: (a) => ({ ...a, ratio: a.matching / a.total }),
+  }).premap((v: In) => ({ matching: predicate(v) ? 1 : 0, total: 1 }));
+}
+
+/**
+ * An aggregate index for calculating an exponentially decaying event rate
+ * from event timestamps.
+ *
+ * Each timestamp `t` contributes `λ * exp(-λ * (now - t))` to the rate, where
+ * `λ = ln(2) / halfLife`. So the result is in events per unit of time (the
+ * unit of the timestamps), and an event counts half as much after every
+ * `halfLife`.
+ *
+ * The result is a function returning the rate at a given time.
+ *
+ * `O(1)` update, and `O(e)` query and memory complexity, where `e` is the
+ * number of distinct epochs of about 11.5 half-lives the timestamps fall in.
+ *
+ * @example
+ * ```typescript
+ * const ixRequestRates = collection.registerIndex(
+ *   group(
+ *     (r) => r.clientId,
+ *     premap((r) => r.timestamp, rateIndex({ halfLife: 10_000 }))
+ *   )
+ * );
+ *
+ * const rate = ixRequestRates.get("client-1")?.get.value()(Date.now());
+ * ```
+ */
+export function rateIndex(args: {
+  halfLife: number;
+}): UnregisteredAggregateIndex<number, (now: number) => number> {
+  if (!(args.halfLife > 0)) {
+    throw new Error("composable-indexes: halfLife must be positive");
+  }
+  const lambda = Math.LN2 / args.halfLife;
+
+  // Contributions are summed per epoch, relative to the start of the epoch,
+  // so that each term is in `[1, exp(EPOCH))`. Deleting a recent event then
+  // can't cancel away the much smaller terms of old ones, and an epoch is
+  // dropped exactly once its last event is deleted.
+  const EPOCH = 8;
+  const epochLength = EPOCH / lambda;
+  type Bucket = { count: number; sum: number };
+  // Created on the first event, since every registration of the index
+  // (e.g. one per group) needs its own.
+  type State = Map<number, Bucket> | undefined;
+
+  const term = (t: number): [number, number] => {
+    const k = Math.floor(t / epochLength);
+    return [k, Math.exp(lambda * (t - k * epochLength))];
+  };
+
+  const add = (st: State, t: number): State => {
+    if (st === undefined) st = new Map();
+    const [k, x] = term(t);
+    const bucket = st.get(k);
+    if (bucket) {
+      bucket.count++;
+      bucket.sum += x;
+    } else {
+      st.set(k, { count: 1, sum: x });
+    }
+    return st;
+  };
+
+  const del = (st: State, t: number): State => {
+    const [k, x] = term(t);
+    const bucket = st?.get(k);
+    if (!bucket) return st;
+    if (--bucket.count === 0) {
+      st!.delete(k);
+    } else {
+      bucket.sum -= x;
+    }
+    return st;
+  };
+
+  return foldIndex<number, State, (now: number) => number>({
+    init: undefined,
+    add,
+    update: (st, oldValue, newValue) => add(del(st, oldValue), newValue),
+    delete: del,
+    result: (st) => (now) => {
+      let ret = 0;
+      for (const [k, { sum }] of st ?? []) {
+        ret += Math.max(0, sum) * Math.exp(lambda * (k * epochLength - now));
+      }
+      return lambda * ret;
+    },
+  });
+}
@@ -0,0 +1,33 @@
+import test from "node:test";
+import fc from "fast-check";
+import { GroupCountsIndex, groupCounts } from "./GroupCountsIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+test("GroupCountsIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        GroupCountsIndex<number, number>,
+        [[number, number][], number]
+      >({
+        valueGen: fc.nat({ max: 30 }),
+        index: groupCounts((v) => v % 4),
+        value: (ix) => [
+          [0, 1, 2, 3].map((g): [number, number] => [g, ix.count(g)]),
+          ix.countDistinct(),
+        ],
+        reference: (arr) => {
+          const counts = [0, 1, 2, 3].map((g): [number, number] => [
+            g,
+            arr.filter((i) => i.value % 4 === g).length,
+          ]);
+          return [counts, counts.filter(([, n]) => n > 0).length];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
@@ -0,0 +1,112 @@
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+
+/**
+ * Counts the items in each group. Unlike {@link groupCount}, which keeps a
+ * {@link GroupedIndex} with an aggregate index per group, this keeps a
+ * single number per group.
+ *
+ * Memory footprint: `O(g)` where `g` is the number of groups
+ *
+ * @see {@link groupCounts} as a constructor.
+ */
+export class GroupCountsIndex<In, Group extends string | number> extends Index<
+  In,
+  any
+> {
+  private readonly counts: Map<Group, number> = new Map();
+
+  private constructor(
+    ctx: IndexContext<any>,
+    private readonly group: (_: In) => Group
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Group extends string | number>(
+    f: (_: In) => Group
+  ): UnregisteredIndex<In, any, GroupCountsIndex<In, Group>> {
+    return new UnregisteredIndex((ctx) => new GroupCountsIndex(ctx, f));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(this.group(update.value), 1);
+      } else if (update.type === UpdateType.UPDATE) {
+        const oldGroup = this.group(update.oldValue);
+        const newGroup = this.group(update.newValue);
+        if (oldGroup !== newGroup) {
+          this.add(oldGroup, -1);
+          this.add(newGroup, 1);
+        }
+      } else if (update.type === UpdateType.DELETE) {
+        this.add(this.group(update.oldValue), -1);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(group: Group, delta: number): void {
+    const count = (this.counts.get(group) ?? 0) + delta;
+    if (count < 0) {
+      this.inconsistent(`Deleted item is not counted in group ${group}`);
+      return;
+    }
+    if (count === 0) {
+      this.counts.delete(group);
+    } else {
+      this.counts.set(group, count);
+    }
+  }
+
+  // Queries
+
+  /**
+   * Complexity: `O(1)`
+   */
+  count(group: Group): number {
+    return this.counts.get(group) ?? 0;
+  }
+
+  /**
+   * The count of every group with items, in no particular order.
+   *
+   * Complexity: `O(1)`; the map is live, so copy it to keep it across
+   * mutations.
+   */
+  all(): ReadonlyMap<Group, number> {
+    return this.counts;
+  }
+
+  /**
+   * The number of groups with items.
+   *
+   * Complexity: `O(1)`
+   */
+  countDistinct(): number {
+    return this.counts.size;
+  }
+}
+
+/**
+ * Create a new {@link GroupCountsIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixTeamSizes = collection.registerIndex(groupCounts((p) => p.team));
+ * console.log(ixTeamSizes.count("Team One"));
+ * ```
+ */
+export function groupCounts<In, Group extends string | number>(
+  f: (_: In) => Group
+): UnregisteredIndex<In, any, GroupCountsIndex<In, Group>> {
+  return GroupCountsIndex.create(f);
+}
@@ -0,0 +1,25 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { groupCount, groupMean, groupSum } from "./GroupedAggregateIndex";
+import { Collection } from "../core/Collection";
+
+type Player = { team: string; score: number };
+
+test("GroupedAggregateIndex", async () => {
+  await test("constructors", () => {
+    const c = new Collection<Player>();
+    const count = c.registerIndex(groupCount((p) => p.team));
+    const sum = c.registerIndex(groupSum((p) => p.team, (p) => p.score));
+    const mean = c.registerIndex(groupMean((p) => p.team, (p) => p.score));
+
+    c.add({ team: "red", score: 1 });
+    c.add({ team: "red", score: 4 });
+    c.add({ team: "blue", score: 3 });
+
+    assert.strictEqual(count.get("red")?.value(), 2);
+    assert.strictEqual(sum.get("red")?.value(), 5);
+    assert.strictEqual(mean.get("red")?.value(), 2.5);
+    assert.strictEqual(mean.get("blue")?.value(), 3);
+    assert.strictEqual(count.get("green"), undefined);
+  });
+});
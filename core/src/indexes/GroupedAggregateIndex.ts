@@ -0,0 +1,56 @@
+import { GroupedIndex, UnregisteredIndex, group } from "../core/Index";
+import { AggregateIndex } from "./AggregateIndex";
+import { arithmeticMeanIndex, countIndex, sumIndex } from "./FoldIndex";
+
+/**
+ * A {@link GroupedIndex} maintaining an aggregate value per group.
+ *
+ * Query it with `ix.get(group)?.value()`.
+ */
+export type GroupedAggregateIndex<
+  In,
+  Group extends string | number,
+  Value
+> = GroupedIndex<In, any, Group, AggregateIndex<In, Value>>;
+
+/**
+ * The number of items in each group.
+ *
+ * @example
+ * ```typescript
+ * const ixTeamSizes = collection.registerIndex(groupCount((p) => p.team));
+ * console.log(ixTeamSizes.get("Team One")?.value());
+ * ```
+ */
+export function groupCount<In, Group extends string | number>(
+  f: (_: In) => Group
+): UnregisteredIndex<In, any, GroupedAggregateIndex<In, Group, number>> {
+  return group(f, countIndex());
+}
+
+/**
+ * The sum of `value` over the items in each group.
+ *
+ * @example
+ * ```typescript
+ * const ixTeamScores = collection.registerIndex(
+ *   groupSum((p) => p.team, (p) => p.score)
+ * );
+ * ```
+ */
+export function groupSum<In, Group extends string | number>(
+  f: (_: In) => Group,
+  value: (_: In) => number
+): UnregisteredIndex<In, any, GroupedAggregateIndex<In, Group, number>> {
+  return group(f, sumIndex().premap(value));
+}
+
+/**
+ * The arithmetic mean of `value` over the items in each group.
+ */
+export function groupMean<In, Group extends string | number>(
+  f: (_: In) => Group,
+  value: (_: In) => number
+): UnregisteredIndex<In, any, GroupedAggregateIndex<In, Group, number>> {
+  return group(f, arithmeticMeanIndex().premap(value));
+}
@@ -0,0 +1,33 @@
+import test from "node:test";
+import fc from "fast-check";
+import { GroupedManyIndex, groupMany } from "./GroupedManyIndex";
+import { AggregateIndex } from "./AggregateIndex";
+import { countIndex } from "./FoldIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+const TAGS = ["a", "b", "c", "d"];
+
+test("GroupedManyIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        string[],
+        GroupedManyIndex<string[], string[], string, AggregateIndex<any, number>>,
+        [string, number, number][]
+      >({
+        valueGen: fc.array(fc.constantFrom(...TAGS), { maxLength: 4 }),
+        index: groupMany((tags) => tags, countIndex()),
+        value: (ix) =>
+          TAGS.map((tag) => [tag, ix.size(tag), ix.get(tag)?.value() ?? 0]),
+        reference: (arr) =>
+          TAGS.map((tag) => {
+            const n = arr.filter((i) => i.value.includes(tag)).length;
+            return [tag, n, n];
+          }),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
@@ -0,0 +1,188 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+
+/**
+ * Like {@link GroupedIndex}, but an item can belong to any number of groups
+ * at once, for example one per tag. An update only touches the groups the
+ * item joins, leaves or stays in.
+ *
+ * Memory footprint: `O(n * k)` on top of the inner indexes, where `k` is the
+ * number of groups per item
+ *
+ * @see {@link groupMany} as a constructor.
+ */
+export class GroupedManyIndex<
+  In,
+  Out,
+  Group extends string | number,
+  Inner extends Index<In, Out>
+> extends Index<In, Out> {
+  private readonly ixs: Map<Group, Inner> = new Map();
+  private readonly sizes: Map<Group, number> = new Map();
+
+  private constructor(
+    private readonly ctx: IndexContext<Out>,
+    private readonly inner: UnregisteredIndex<In, Out, Inner>,
+    private readonly groupsOf: (_: In) => Iterable<Group>
+  ) {
+    super(ctx);
+  }
+
+  static create<
+    In,
+    Out,
+    Group extends string | number,
+    Inner extends Index<In, Out>
+  >(
+    f: (_: In) => Iterable<Group>,
+    inner: UnregisteredIndex<In, Out, Inner>
+  ): UnregisteredIndex<In, Out, GroupedManyIndex<In, Out, Group, Inner>> {
+    return new UnregisteredIndex(
+      (ctx) => new GroupedManyIndex(ctx, inner, f)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    const hooks: (() => void)[] = [];
+
+    if (update.type === UpdateType.ADD) {
+      for (const group of new Set(this.groupsOf(update.value))) {
+        hooks.push(this.add(group, update.id, update.value));
+      }
+    } else if (update.type === UpdateType.UPDATE) {
+      const oldGroups = new Set(this.groupsOf(update.oldValue));
+      const newGroups = new Set(this.groupsOf(update.newValue));
+      for (const group of oldGroups) {
+        if (newGroups.has(group)) {
+          hooks.push(this.stay(group, update));
+        } else {
+          hooks.push(this.delete(group, update.id, update.oldValue));
+        }
+      }
+      for (const group of newGroups) {
+        if (!oldGroups.has(group)) {
+          hooks.push(this.add(group, update.id, update.newValue));
+        }
+      }
+    } else if (update.type === UpdateType.DELETE) {
+      for (const group of new Set(this.groupsOf(update.oldValue))) {
+        hooks.push(this.delete(group, update.id, update.oldValue));
+      }
+    } else {
+      unreachable(update);
+    }
+
+    return () => {
+      for (const hook of hooks) {
+        hook();
+      }
+    };
+  }
+
+  private add(group: Group, id: Id, value: In): () => void {
+    // A new group is only stored once the inner index accepts the item, so
+    // that a rejected item does not leave an empty group behind.
+    const ix = this.ixs.get(group) ?? this.inner._register(this.ctx);
+    const hook = ix._onUpdate({ type: UpdateType.ADD, id, value });
+    return () => {
+      hook();
+      this.ixs.set(group, ix);
+      this.sizes.set(group, (this.sizes.get(group) ?? 0) + 1);
+    };
+  }
+
+  private stay(group: Group, update: Update<In>): () => void {
+    const ix = this.ixs.get(group);
+    if (!ix) {
+      this.inconsistent(`Updated item ${update.id} is not in group ${group}`);
+      return () => {};
+    }
+    return ix._onUpdate(update);
+  }
+
+  private delete(group: Group, id: Id, oldValue: In): () => void {
+    const ix = this.ixs.get(group);
+    if (!ix) {
+      this.inconsistent(`Deleted item ${id} is not in group ${group}`);
+      return () => {};
+    }
+    const hook = ix._onUpdate({ type: UpdateType.DELETE, id, oldValue });
+    return () => {
+      hook();
+      const size = (this.sizes.get(group) ?? 0) - 1;
+      if (size > 0) {
+        this.sizes.set(group, size);
+      } else {
+        this.sizes.delete(group);
+        this.ixs.delete(group);
+      }
+    };
+  }
+
+  // Queries
+
+  /**
+   * The index of the given group, or `undefined` if the group has no items.
+   *
+   * Complexity: `O(1)`
+   */
+  get(group: Group): Inner | undefined {
+    return this.ixs.get(group);
+  }
+
+  /**
+   * The number of items in the group.
+   *
+   * Complexity: `O(1)`
+   */
+  size(group: Group): number {
+    return this.sizes.get(group) ?? 0;
+  }
+
+  /**
+   * Iterates over the groups and their indexes, in no particular order.
+   */
+  groups(): IterableIterator<[Group, Inner]> {
+    return this.ixs.entries();
+  }
+
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters: { groups: this.ixs.size },
+      children: [this.inner._register(this.ctx).describe()],
+    };
+  }
+}
+
+/**
+ * Create a new {@link GroupedManyIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixByTag = collection.registerIndex(
+ *   groupMany((post) => post.tags, countIndex())
+ * );
+ *
+ * console.log(ixByTag.get("typescript")?.value());
+ * ```
+ */
+export function groupMany<
+  In,
+  Out,
+  Group extends string | number,
+  Inner extends Index<In, Out>
+>(
+  f: (_: In) => Iterable<Group>,
+  inner: UnregisteredIndex<In, Out, Inner>
+): UnregisteredIndex<In, Out, GroupedManyIndex<In, Out, Group, Inner>> {
+  return GroupedManyIndex.create(f, inner);
+}
@@ -3,6 +3,10 @@ import { HashIndex, hashIndex } from "./HashIndex";
 import fc from "fast-check";
 import { propIndexAgainstReference } from "../test_util/reference";
 import { testProps } from "../test_util/invariants";
+import { arbCalls, playCalls } from "../test_util/call";
+import { Collection } from "../core/Collection";
+import { premap } from "../core/Index";
+import { strict as assert } from "node:assert";
 
 test("HashIndex", async () => {
   await test("HashIndex.ref", () => {
@@ -19,6 +23,101 @@ test("HashIndex", async () => {
     )
   });
 
+  await test("HashIndex.ref.eqAll", () => {
+    fc.assert(
+      propIndexAgainstReference<number, HashIndex<number, number>, [number, string[]][]>({
+        valueGen: fc.integer({ min: 0, max: 5 }),
+        index: hashIndex(),
+        value: (ix) =>
+          Array.from(ix.eqAll([3, 0, 7, 3]), ([v, items]): [number, string[]] => [
+            v,
+            items.map((i) => i.id.toString()).sort(),
+          ]),
+        reference: (arr) =>
+          [3, 0, 7].map((v): [number, string[]] => [
+            v,
+            arr.filter((i) => i.value === v).map((i) => i.id.toString()).sort(),
+          ]),
+      }),
+      {
+        numRuns: 10000,
+      }
+    )
+  });
+
+  await test("HashIndex.ref.exists", () => {
+    const keys = new Set([1, 3, 6]);
+    fc.assert(
+      propIndexAgainstReference<number, HashIndex<number, number>, [string[], string[]]>({
+        valueGen: fc.integer({ min: 0, max: 5 }),
+        index: hashIndex(),
+        value: (ix) => [
+          ix.exists(keys).map((i) => i.id.toString()).sort(),
+          ix.notExists(keys).map((i) => i.id.toString()).sort(),
+        ],
+        reference: (arr) => [
+          arr.filter((i) => keys.has(i.value)).map((i) => i.id.toString()).sort(),
+          arr.filter((i) => !keys.has(i.value)).map((i) => i.id.toString()).sort(),
+        ],
+      }),
+      {
+        numRuns: 10000,
+      }
+    )
+  });
+
+  await test("HashIndex.notExists across collections", () => {
+    const customers = new Collection<string>();
+    const orders = new Collection<{ customer: string }>();
+    const ixCustomers = customers.registerIndex(hashIndex());
+    const ixOrders = orders.registerIndex(
+      premap((o: { customer: string }) => o.customer, hashIndex())
+    );
+
+    customers.add("alice");
+    const bob = customers.add("bob");
+    orders.addAll([{ customer: "alice" }, { customer: "bob" }, { customer: "carol" }]);
+    customers.delete(bob);
+
+    assert.deepEqual(
+      ixOrders.get.notExists(ixCustomers).map((i) => i.value.customer).sort(),
+      ["bob", "carol"]
+    );
+    assert.deepEqual(
+      ixOrders.get.exists(ixCustomers).map((i) => i.value.customer),
+      ["alice"]
+    );
+  });
+
+  await test("HashIndex.ref.distinctValues", () => {
+    fc.assert(
+      fc.property(
+        arbCalls({ value: fc.integer({ min: 0, max: 5 }), idRange: 10, maxLength: 100 }),
+        (calls) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(hashIndex<number, number>());
+
+          // Replays the calls one by one, tracking the order values appear in.
+          const order: number[] = [];
+          for (const call of calls) {
+            playCalls(c, [call]);
+            const present = new Set(c.toList().map(([, v]) => v));
+            for (let i = order.length - 1; i >= 0; i--) {
+              if (!present.has(order[i])) order.splice(i, 1);
+            }
+            for (const v of present) {
+              if (!order.includes(v)) order.push(v);
+            }
+            assert.deepEqual(ix.distinctValues(), order);
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
   await test("HashIndex.ref.countDistinct", () => {
     fc.assert(
       propIndexAgainstReference<number, HashIndex<number, number>, number>({
@@ -39,6 +138,30 @@ test("HashIndex", async () => {
     )
   });
 
+  await test("HashIndex.ref.countDistribution", () => {
+    fc.assert(
+      propIndexAgainstReference<number, HashIndex<number, number>, [number, number][]>({
+        valueGen: fc.integer({ min: 0, max: 5 }),
+        index: hashIndex(),
+        value: (ix) => Array.from(ix.countDistribution()).sort(),
+        reference: (arr) => {
+          const counts = new Map<number, number>();
+          for (const it of arr) {
+            counts.set(it.value, (counts.get(it.value) ?? 0) + 1);
+          }
+          const ret = new Map<number, number>();
+          for (const count of counts.values()) {
+            ret.set(count, (ret.get(count) ?? 0) + 1);
+          }
+          return Array.from(ret).sort();
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    )
+  });
+
   const witnesses = {
     eq: (ix: HashIndex<number, number>) =>
       ix
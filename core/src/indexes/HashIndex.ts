@@ -2,12 +2,19 @@ import { Id } from "..";
 import {
   Index,
   IndexContext,
+  IndexDescription,
   UnregisteredIndex,
 } from "../core/Index";
 import { Update, UpdateType } from "../core/Update";
 import { Item } from "../core/simple_types";
 import { IdSet, unreachable } from "../util";
- 
+
+/**
+ * Anything that can tell whether it has a key, like a `Set`, a
+ * {@link HashIndex}, a {@link UniqueHashIndex} or a {@link GroupedIndex}.
+ */
+export type KeySet<K> = { has(key: K): boolean };
+
 /**
  * Indexes items using a JavaScript `Map`.
  */
@@ -48,31 +55,170 @@ export class HashIndex<In extends number | string, Out> extends Index<In, Out> {
   }
 
   private update(id: Id, oldValue: In, newValue: In): void {
+    // Re-adding would move the value to the end of `distinctValues`.
+    if (oldValue === newValue) return;
     this.delete(id, oldValue);
     this.add(id, newValue);
   }
 
   private delete(id: Id, oldValue: In): void {
     const set = this.ix.get(oldValue);
-    set!.delete(id);
-    if (set && set.empty()) {
+    if (!set?.has(id)) {
+      this.inconsistent(`Deleted item ${id} is not indexed under ${oldValue}`);
+      return;
+    }
+    set.delete(id);
+    if (set.empty()) {
       this.ix.delete(oldValue);
     }
   }
 
   // Queries
+
+  /**
+   * Includes the number of distinct values, and the most items sharing a
+   * value.
+   */
+  override describe(): IndexDescription {
+    let maxItemsPerValue = 0;
+    for (const set of this.ix.values()) {
+      maxItemsPerValue = Math.max(maxItemsPerValue, set.size);
+    }
+    return {
+      ...super.describe(),
+      parameters: { distinctValues: this.ix.size, maxItemsPerValue },
+    };
+  }
+
   countDistinct(): number {
     return this.ix.size;
   }
 
-  eq(value: In): Item<Out>[] {
-    return this.items(this.ix.get(value));
+  /**
+   * The distinct values, in the order they were added; a value whose items
+   * are all deleted moves to the end when it is added again. Useful to list
+   * facets in a stable order without sorting.
+   *
+   * Complexity: `O(d)` where `d` is the number of distinct values
+   */
+  distinctValues(): In[] {
+    return Array.from(this.ix.keys());
+  }
+
+  /**
+   * For each number of items sharing a value, how many distinct values have
+   * that many items. Useful to see how skewed the indexed values are.
+   *
+   * Complexity: `O(d)` where `d` is the number of distinct values
+   */
+  countDistribution(): Map<number, number> {
+    const ret: Map<number, number> = new Map();
+    for (const set of this.ix.values()) {
+      ret.set(set.size, (ret.get(set.size) ?? 0) + 1);
+    }
+    return ret;
+  }
+
+  /**
+   * Groups of items that share a value, for every value that more than one
+   * item has. Pass them to {@link Collection.dedup} to remove the duplicates.
+   *
+   * Complexity: `O(d + m)` where `d` is the number of distinct values and `m`
+   * is the number of items fetched
+   */
+  duplicates(): Item<Out>[][] {
+    const ret: Item<Out>[][] = [];
+    for (const set of this.ix.values()) {
+      if (set.size > 1) {
+        ret.push(this.items(set));
+      }
+    }
+    return ret;
+  }
+
+  /**
+   * Whether any item has the value.
+   *
+   * Complexity: `O(1)`
+   */
+  has(value: In): boolean {
+    return this.ix.has(value);
+  }
+
+  /**
+   * The items whose value is in `keys`, like an `EXISTS` subquery. Useful
+   * with an index on another collection, for example to find the orders of
+   * the customers that are still active.
+   *
+   * Complexity: `O(d + m)` where `d` is the number of distinct values and `m`
+   * is the number of items fetched
+   */
+  exists(keys: KeySet<In>): Item<Out>[] {
+    return this.matching(keys, true);
+  }
+
+  /**
+   * The items whose value is not in `keys`, like a `NOT EXISTS` subquery.
+   * Useful to find dangling references, for example orders whose customer
+   * no longer exists.
+   *
+   * @example
+   * ```typescript
+   * const ixOrdersByCustomer = orders.registerIndex(
+   *   premap((o) => o.customerId, hashIndex())
+   * );
+   * const ixCustomers = customers.registerIndex(
+   *   premap((c) => c.id, uniqueHashIndex())
+   * );
+   *
+   * const orphans = ixOrdersByCustomer.get.notExists(ixCustomers.get);
+   * ```
+   *
+   * Complexity: `O(d + m)` where `d` is the number of distinct values and `m`
+   * is the number of items fetched
+   */
+  notExists(keys: KeySet<In>): Item<Out>[] {
+    return this.matching(keys, false);
+  }
+
+  /**
+   * @param into An array to append the results to, instead of a new one.
+   * Reusing an array avoids an allocation per query on hot paths.
+   */
+  eq(value: In, into: Item<Out>[] = []): Item<Out>[] {
+    return this.items(this.ix.get(value), into);
+  }
+
+  /**
+   * Looks up several values at once, for example to validate a batch of
+   * incoming identifiers. Values without items are included with an empty
+   * array, and duplicate values are looked up once.
+   *
+   * Complexity: `O(v + m)` where `v` is the number of values and `m` is the
+   * number of items fetched
+   */
+  eqAll(values: Iterable<In>): Map<In, Item<Out>[]> {
+    const ret: Map<In, Item<Out>[]> = new Map();
+    for (const value of values) {
+      if (!ret.has(value)) {
+        ret.set(value, this.items(this.ix.get(value)));
+      }
+    }
+    return ret;
   }
 
   // Utils
-  private items(set: IdSet | undefined): Item<Out>[] {
+  private matching(keys: KeySet<In>, present: boolean): Item<Out>[] {
     const ret: Item<Out>[] = [];
+    for (const [value, set] of this.ix) {
+      if (keys.has(value) === present) {
+        this.items(set, ret);
+      }
+    }
+    return ret;
+  }
 
+  private items(set: IdSet | undefined, ret: Item<Out>[] = []): Item<Out>[] {
     if (!set) return ret;
     set.forEach((id) => {
       ret.push(this.item(id));
@@ -0,0 +1,56 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { lastNIndex } from "./LastNIndex";
+import { sumIndex } from "./FoldIndex";
+import { Collection } from "../core/Collection";
+import { arbCalls } from "../test_util/call";
+
+test("LastNIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      fc.property(
+        fc.integer({ min: 0, max: 4 }),
+        arbCalls({ value: fc.integer({ min: 0, max: 20 }), idRange: 10, maxLength: 100 }),
+        (n, calls) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(lastNIndex(n, sumIndex()));
+          // The items in the window, oldest first.
+          let window: [string, number][] = [];
+
+          const push = (id: string, value: number) => {
+            if (n === 0) return;
+            window.push([id, value]);
+            if (window.length > n) window.shift();
+          };
+
+          for (const call of calls) {
+            if (call.type === "add") {
+              push(c.add(call.value).toString(), call.value);
+            } else if (call.type === "set") {
+              const key = call.id.toString();
+              const existed = c.get(call.id) !== undefined;
+              c.set(call.id, call.value);
+              if (!existed) {
+                push(key, call.value);
+              } else {
+                window = window.map(([k, v]) => [k, k === key ? call.value : v]);
+              }
+            } else {
+              c.delete(call.id);
+              window = window.filter(([k]) => k !== call.id.toString());
+            }
+
+            assert.strictEqual(
+              ix.value(),
+              window.reduce((acc, [, v]) => acc + v, 0)
+            );
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
@@ -0,0 +1,137 @@
+import { Id } from "..";
+import { IndexContext } from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { IdMap, unreachable } from "../util";
+import { AggregateIndex, UnregisteredAggregateIndex } from "./AggregateIndex";
+
+type Member<In> = { value: In; seq: number };
+
+/**
+ * An aggregate index over only the `n` most recently added items.
+ *
+ * Adding an item evicts the oldest one from the inner index once there are
+ * `n` of them. Deleting an item shrinks the window; already evicted items do
+ * not come back. Updates and deletions of evicted items are ignored.
+ *
+ * Memory footprint: `O(n)`, plus the footprint of the inner index.
+ *
+ * @see {@link lastNIndex} as a constructor.
+ */
+export class LastNIndex<In, Value> extends AggregateIndex<In, Value> {
+  private readonly members: IdMap<Member<In>> = new IdMap();
+  // Members in the order they were added. Entries of deleted members are
+  // skipped, and dropped from time to time.
+  private queue: { id: Id; seq: number }[] = [];
+  private head = 0;
+  private seq = 0;
+
+  private constructor(
+    ctx: IndexContext<any>,
+    private readonly inner: AggregateIndex<In, Value>,
+    private readonly n: number
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Value>(
+    n: number,
+    inner: UnregisteredAggregateIndex<In, Value>
+  ): UnregisteredAggregateIndex<In, Value> {
+    return new UnregisteredAggregateIndex(
+      (ctx) => new LastNIndex(ctx, inner._register(ctx), n)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    if (update.type === UpdateType.ADD) {
+      return this.add(update.id, update.value);
+    } else if (update.type === UpdateType.UPDATE) {
+      const member = this.members.get(update.id);
+      if (!member) return () => {};
+      const hook = this.inner._onUpdate(update);
+      return () => {
+        hook();
+        member.value = update.newValue;
+      };
+    } else if (update.type === UpdateType.DELETE) {
+      if (!this.members.get(update.id)) return () => {};
+      const hook = this.inner._onUpdate(update);
+      return () => {
+        hook();
+        this.members.delete(update.id);
+      };
+    } else {
+      unreachable(update);
+    }
+  }
+
+  private add(id: Id, value: In): () => void {
+    if (this.n <= 0) return () => {};
+
+    const oldest = this.members.size >= this.n ? this.oldest() : undefined;
+    const evictHook = oldest
+      ? this.inner._onUpdate({
+          type: UpdateType.DELETE,
+          id: oldest.id,
+          oldValue: oldest.value,
+        })
+      : () => {};
+    const hook = this.inner._onUpdate({ type: UpdateType.ADD, id, value });
+
+    return () => {
+      evictHook();
+      hook();
+      if (oldest) {
+        this.members.delete(oldest.id);
+        this.head++;
+      }
+      const seq = this.seq++;
+      this.members.set(id, { value, seq });
+      this.queue.push({ id, seq });
+      this.compact();
+    };
+  }
+
+  // The oldest member, skipping the entries of deleted ones.
+  private oldest(): { id: Id; value: In } | undefined {
+    while (this.head < this.queue.length) {
+      const { id, seq } = this.queue[this.head];
+      const member = this.members.get(id);
+      if (member && member.seq === seq) {
+        return { id, value: member.value };
+      }
+      this.head++;
+    }
+    return undefined;
+  }
+
+  private compact(): void {
+    if (this.head > 32 && this.head * 2 > this.queue.length) {
+      this.queue = this.queue.slice(this.head);
+      this.head = 0;
+    }
+  }
+
+  override value(): Value {
+    return this.inner.value();
+  }
+}
+
+/**
+ * Create a new {@link LastNIndex}.
+ *
+ * @example
+ * ```typescript
+ * // Average latency of the last 100 requests
+ * const ixRecentLatency = collection.registerIndex(
+ *   lastNIndex(100, arithmeticMeanIndex()).premap((r) => r.latency)
+ * );
+ * ```
+ */
+export function lastNIndex<In, Value>(
+  n: number,
+  inner: UnregisteredAggregateIndex<In, Value>
+): UnregisteredAggregateIndex<In, Value> {
+  return LastNIndex.create(n, inner);
+}
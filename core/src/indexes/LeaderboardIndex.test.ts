@@ -0,0 +1,98 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { LeaderboardIndex, leaderboardIndex } from "./LeaderboardIndex";
+import { Collection } from "../core/Collection";
+import { premap } from "../core/Index";
+import { Item } from "../core/simple_types";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+// Highest first, then oldest first.
+function ranked(arr: Item<number>[]): Item<number>[] {
+  return [...arr].sort((a, b) => b.value - a.value || a.id.compare(b.id));
+}
+
+test("LeaderboardIndex", async () => {
+  await test("LeaderboardIndex.ref.top", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        LeaderboardIndex<number, number>,
+        Item<number>[]
+      >({
+        valueGen: fc.integer({ min: 0, max: 10 }),
+        index: leaderboardIndex(),
+        value: (ix) => ix.top(3),
+        reference: (arr) => ranked(arr).slice(0, 3),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("LeaderboardIndex.ref.rankOf", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        LeaderboardIndex<number, number>,
+        [string, number | undefined][]
+      >({
+        valueGen: fc.integer({ min: 0, max: 10 }),
+        index: leaderboardIndex(),
+        value: (ix) =>
+          ranked(ix.top(ix.size())).map((i) => [
+            i.id.toString(),
+            ix.rankOf(i.id),
+          ]),
+        reference: (arr) =>
+          ranked(arr).map((i, rank) => [i.id.toString(), rank]),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("LeaderboardIndex.ref.percentileOf", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        LeaderboardIndex<number, number>,
+        (number | undefined)[]
+      >({
+        valueGen: fc.integer({ min: 0, max: 10 }),
+        index: leaderboardIndex(),
+        value: (ix) => [0, 3, 5, 11].map((v) => ix.percentileOf(v)),
+        reference: (arr) =>
+          [0, 3, 5, 11].map((v) =>
+            arr.length === 0
+              ? undefined
+              : (arr.filter((i) => i.value < v).length / arr.length) * 100
+          ),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("composite keys", () => {
+    type Score = { name: string; score: number; at: number };
+    const c = new Collection<Score>();
+    const ix = c.registerIndex(
+      premap((s: Score) => [s.score, -s.at], leaderboardIndex<number[], Score>())
+    );
+
+    c.add({ name: "a", score: 10, at: 2 });
+    const b = c.add({ name: "b", score: 10, at: 1 });
+    c.add({ name: "c", score: 5, at: 0 });
+    c.add({ name: "d", score: 20, at: 3 });
+
+    const names = (items: Item<Score>[]) => items.map((i) => i.value.name);
+    assert.deepEqual(names(ix.get.top(4)), ["d", "b", "a", "c"]);
+    assert.strictEqual(ix.get.rankOf(b), 1);
+    assert.deepEqual(names(ix.get.around(b, 1)), ["d", "b", "a"]);
+    assert.strictEqual(ix.get.at(3)?.value.name, "c");
+  });
+});
@@ -0,0 +1,189 @@
+import Long from "long";
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdMap, RankTree, unreachable } from "../util";
+
+/**
+ * A key to rank items by. Arrays are compared element by element, so
+ * `[score, -timestamp]` ranks by score, and then earlier timestamps first.
+ */
+export type RankKey = number | string | readonly (number | string)[];
+
+/**
+ * Ranks the items by a key, with the largest key at rank `0`. Items with
+ * equal keys are ranked by their {@link Id}, so older items come first.
+ *
+ * Memory footprint: `O(n)`
+ *
+ * @see {@link leaderboardIndex} as a constructor.
+ */
+export class LeaderboardIndex<In extends RankKey, Out> extends Index<In, Out> {
+  // Ordered with the highest ranked entry first.
+  private readonly tree = new RankTree<[In, Id]>(
+    (a, b) => compareKeys(b[0], a[0]) || a[1].compare(b[1])
+  );
+  private readonly keys: IdMap<In> = new IdMap();
+
+  private constructor(ctx: IndexContext<Out>) {
+    super(ctx);
+  }
+
+  static create<In extends RankKey, Out>(): UnregisteredIndex<
+    In,
+    Out,
+    LeaderboardIndex<In, Out>
+  > {
+    return new UnregisteredIndex((ctx) => new LeaderboardIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.id);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, key: In): void {
+    this.keys.set(id, key);
+    this.tree.insert([key, id]);
+  }
+
+  private delete(id: Id): void {
+    const key = this.keys.get(id);
+    if (key === undefined) {
+      this.inconsistent(`Deleted item ${id} is not ranked`);
+      return;
+    }
+    this.keys.delete(id);
+    this.tree.delete([key, id]);
+  }
+
+  // Queries
+
+  /**
+   * The rank of the item, counting from zero, or `undefined` if it is not in
+   * the collection.
+   *
+   * Complexity: `O(log(n))`
+   */
+  rankOf(id: Id): number | undefined {
+    const key = this.keys.get(id);
+    return key === undefined ? undefined : this.tree.rank([key, id]);
+  }
+
+  /**
+   * The item at the given rank.
+   *
+   * Complexity: `O(log(n))`
+   */
+  at(rank: number): Item<Out> | undefined {
+    const entry = this.tree.at(rank);
+    return entry && this.item(entry[1]);
+  }
+
+  /**
+   * The `n` highest ranked items, in rank order.
+   *
+   * Complexity: `O(n * log(N))`
+   */
+  top(n: number): Item<Out>[] {
+    return this.slice(0, n);
+  }
+
+  /**
+   * The item with up to `n` items ranked just above and just below it, in
+   * rank order.
+   *
+   * Complexity: `O(n * log(N))`
+   */
+  around(id: Id, n: number): Item<Out>[] {
+    const rank = this.rankOf(id);
+    if (rank === undefined) return [];
+    return this.slice(Math.max(0, rank - n), rank + n + 1);
+  }
+
+  /**
+   * The percentage of items with a key smaller than `key`, or `undefined` if
+   * there are no items. For example, the percentile of a request's latency
+   * tells how many requests were faster.
+   *
+   * Complexity: `O(log(n))`
+   */
+  percentileOf(key: In): number | undefined {
+    const size = this.tree.size;
+    if (size === 0) return undefined;
+    // Entries are ordered largest key first, and by id among equal keys, so
+    // everything before the largest possible id has a key of at least `key`.
+    const atLeast = this.tree.rank([key, MAX_ID]);
+    return ((size - atLeast) / size) * 100;
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  size(): number {
+    return this.tree.size;
+  }
+
+  // Utils
+  private slice(start: number, end: number): Item<Out>[] {
+    const ret: Item<Out>[] = [];
+    for (let rank = start; rank < Math.min(end, this.tree.size); rank++) {
+      ret.push(this.item(this.tree.at(rank)![1]));
+    }
+    return ret;
+  }
+}
+
+const MAX_ID = Id.fromLong(Long.MAX_UNSIGNED_VALUE);
+
+function compareKeys(a: RankKey, b: RankKey): number {
+  if (Array.isArray(a) && Array.isArray(b)) {
+    for (let i = 0; i < Math.min(a.length, b.length); i++) {
+      const c = compareScalars(a[i], b[i]);
+      if (c !== 0) return c;
+    }
+    return a.length - b.length;
+  }
+  return compareScalars(a as number | string, b as number | string);
+}
+
+function compareScalars(a: number | string, b: number | string): number {
+  return a < b ? -1 : a > b ? 1 : 0;
+}
+
+/**
+ * Create a new {@link LeaderboardIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixLeaderboard = collection.registerIndex(
+ *   premap((p) => [p.score, -p.timestamp], leaderboardIndex())
+ * );
+ *
+ * const podium = ixLeaderboard.get.top(3);
+ * const myRank = ixLeaderboard.get.rankOf(myId);
+ * ```
+ */
+export function leaderboardIndex<In extends RankKey, Out>(): UnregisteredIndex<
+  In,
+  Out,
+  LeaderboardIndex<In, Out>
+> {
+  return LeaderboardIndex.create();
+}
@@ -0,0 +1,39 @@
+import test from "node:test";
+import { medianIndex } from "./MedianIndex";
+import { AggregateIndex } from "./AggregateIndex";
+import fc from "fast-check";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { testProps } from "../test_util/invariants";
+
+type Ix = AggregateIndex<number, number | undefined>;
+
+test("MedianIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<number, Ix, number | undefined>({
+        valueGen: fc.integer({ min: 0, max: 20 }),
+        index: medianIndex(),
+        value: (ix) => ix.value(),
+        reference: (arr) => {
+          const values = arr.map((it) => it.value).sort((a, b) => a - b);
+          if (values.length === 0) return undefined;
+          const mid = Math.floor(values.length / 2);
+          return values.length % 2 === 1
+            ? values[mid]
+            : (values[mid - 1] + values[mid]) / 2;
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("props", async (t) => {
+    await testProps<number, Ix, number | undefined>(t, {
+      valueGen: fc.integer(),
+      index: medianIndex(),
+      witness: (ix) => ix.value(),
+    });
+  });
+});
@@ -0,0 +1,94 @@
+import { IndexContext } from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { LazyHeap, unreachable } from "../util";
+import { AggregateIndex, UnregisteredAggregateIndex } from "./AggregateIndex";
+
+/**
+ * An aggregate index for calculating the exact median.
+ *
+ * Keeps the lower half of the values in a max-heap and the upper half in a
+ * min-heap. Removed values are only dropped from a heap once they reach its
+ * top.
+ *
+ * `O(1)` query, `O(log(n))` amortized update, and `O(n + d)` memory
+ * complexity, where `d` is the number of removed values not yet dropped.
+ *
+ * @see {@link medianIndex} as a constructor.
+ */
+export class MedianIndex extends AggregateIndex<number, number | undefined> {
+  private readonly low = new LazyHeap((a, b) => a > b);
+  private readonly high = new LazyHeap((a, b) => a < b);
+
+  private constructor(ctx: IndexContext<any>) {
+    super(ctx);
+  }
+
+  static create(): UnregisteredAggregateIndex<number, number | undefined> {
+    return new UnregisteredAggregateIndex((ctx) => new MedianIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<number>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.oldValue);
+        this.add(update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(value: number): void {
+    if (this.low.size === 0 || value <= this.low.peek()!) {
+      this.low.push(value);
+    } else {
+      this.high.push(value);
+    }
+    this.rebalance();
+  }
+
+  private delete(value: number): void {
+    // Every value in `low` is at most every value in `high`, so a value not
+    // greater than the top of `low` must be in `low`.
+    if (value <= this.low.peek()!) {
+      this.low.remove(value);
+    } else {
+      this.high.remove(value);
+    }
+    this.rebalance();
+  }
+
+  // Keeps `low` either the same size as `high`, or one element larger.
+  private rebalance(): void {
+    if (this.low.size > this.high.size + 1) {
+      this.high.push(this.low.pop()!);
+    } else if (this.low.size < this.high.size) {
+      this.low.push(this.high.pop()!);
+    }
+  }
+
+  override value(): number | undefined {
+    if (this.low.size === 0) {
+      return undefined;
+    } else if (this.low.size > this.high.size) {
+      return this.low.peek();
+    } else {
+      return (this.low.peek()! + this.high.peek()!) / 2;
+    }
+  }
+}
+
+/**
+ * Create a new {@link MedianIndex}.
+ */
+export function medianIndex(): UnregisteredAggregateIndex<
+  number,
+  number | undefined
+> {
+  return MedianIndex.create();
+}
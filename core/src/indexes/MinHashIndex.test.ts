@@ -0,0 +1,69 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { Collection } from "../core/Collection";
+import { arbCalls, playCalls } from "../test_util/call";
+import { minHashIndex } from "./MinHashIndex";
+
+test("MinHashIndex", async () => {
+  await test("equal strings are always candidates", () => {
+    fc.assert(
+      fc.property(
+        arbCalls({
+          value: fc.constantFrom("lorem ipsum", "lorem ipsun", "dolor sit", "amet"),
+          idRange: 10,
+          maxLength: 50,
+        }),
+        (calls) => {
+          const c = new Collection<string>();
+          const ix = c.registerIndex(minHashIndex({ numHashes: 16, bandSize: 4 }));
+          playCalls(c, calls);
+
+          const pairs = new Set(ix.candidatePairs().map(([a, b]) => `${a}:${b}`));
+          const items = c.toList();
+          for (const [a, va] of items) {
+            const dupes = ix.nearDuplicatesOf(a, 1).map((s) => s.item.id.toString());
+            for (const [b, vb] of items) {
+              if (a.compare(b) >= 0 || va !== vb) continue;
+              assert.strictEqual(ix.similarity(a, b), 1);
+              assert.ok(pairs.has(`${a}:${b}`));
+              assert.ok(dupes.includes(b.toString()));
+            }
+            assert.ok(!dupes.includes(a.toString()));
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("similarTo", () => {
+    const c = new Collection<string>();
+    const ix = c.registerIndex(minHashIndex({ numHashes: 64, bandSize: 2 }));
+    const close = c.add("the quick brown fox jumps over the lazy dog");
+    c.add("an entirely unrelated sentence about databases");
+
+    const found = ix.similarTo("the quick brown fox jumped over the lazy dog", 0.5);
+    assert.deepEqual(
+      found.map((s) => s.item.id.toString()),
+      [close.toString()]
+    );
+    assert.ok(found[0].similarity < 1);
+
+    c.delete(close);
+    assert.deepEqual(ix.similarTo("the quick brown fox jumps over the lazy dog", 0.5), []);
+  });
+
+  await test("invalid parameters", () => {
+    assert.throws(() => minHashIndex({ numHashes: 0, bandSize: 1 }), /numHashes/);
+    assert.throws(() => minHashIndex({ numHashes: 8, bandSize: 0 }), /bandSize/);
+    assert.throws(() => minHashIndex({ numHashes: 8, bandSize: 9 }), /bandSize/);
+    assert.throws(
+      () => minHashIndex({ numHashes: 8, bandSize: 2, shingleSize: 0 }),
+      /shingleSize/
+    );
+    assert.throws(() => minHashIndex({ numHashes: NaN, bandSize: 1 }), /numHashes/);
+  });
+});
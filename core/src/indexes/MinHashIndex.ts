@@ -0,0 +1,269 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdMap, IdSet, fnv1a, unreachable } from "../util";
+
+/**
+ * An item along with its estimated similarity to another.
+ */
+export type Similar<Out> = { item: Item<Out>; similarity: number };
+
+/**
+ * Finds near-duplicate strings using
+ * [MinHash](https://en.wikipedia.org/wiki/MinHash) signatures and
+ * locality-sensitive hashing.
+ *
+ * Strings are split into overlapping character `shingleSize`-grams. The
+ * signature of a string is the minimum of each of `numHashes` hash functions
+ * over its shingles; the fraction of equal positions in two signatures
+ * estimates the [Jaccard
+ * similarity](https://en.wikipedia.org/wiki/Jaccard_index) of their shingles.
+ * Signatures are split into bands of `bandSize` hashes, and strings sharing
+ * any band are candidates. Larger bands find fewer, more similar candidates.
+ *
+ * Results are approximate: a similar string can be missed, and similarities
+ * are estimates.
+ *
+ * Memory footprint: `O(n * numHashes)`
+ *
+ * @see {@link minHashIndex} as a constructor.
+ */
+export class MinHashIndex<Out> extends Index<string, Out> {
+  private readonly signatures: IdMap<Uint32Array> = new IdMap();
+  private readonly buckets: Map<string, IdSet> = new Map();
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly numHashes: number,
+    private readonly bandSize: number,
+    private readonly shingleSize: number
+  ) {
+    super(ctx);
+  }
+
+  static create<Out>(args: {
+    numHashes: number;
+    bandSize: number;
+    shingleSize: number;
+  }): UnregisteredIndex<string, Out, MinHashIndex<Out>> {
+    if (!(args.numHashes >= 1)) {
+      throw new Error("composable-indexes: numHashes must be at least 1");
+    }
+    if (!(args.bandSize >= 1 && args.bandSize <= args.numHashes)) {
+      throw new Error(
+        "composable-indexes: bandSize must be between 1 and numHashes"
+      );
+    }
+    if (!(args.shingleSize >= 1)) {
+      throw new Error("composable-indexes: shingleSize must be at least 1");
+    }
+    return new UnregisteredIndex(
+      (ctx) =>
+        new MinHashIndex(ctx, args.numHashes, args.bandSize, args.shingleSize)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<string>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.id);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, value: string): void {
+    const signature = this.signature(value);
+    this.signatures.set(id, signature);
+    for (const band of this.bands(signature)) {
+      const set = this.buckets.get(band);
+      if (set) {
+        set.set(id);
+      } else {
+        this.buckets.set(band, IdSet.singleton(id));
+      }
+    }
+  }
+
+  private delete(id: Id): void {
+    const signature = this.signatures.get(id);
+    if (!signature) {
+      this.inconsistent(`Deleted item ${id} is not indexed`);
+      return;
+    }
+    this.signatures.delete(id);
+    for (const band of this.bands(signature)) {
+      const set = this.buckets.get(band);
+      set?.delete(id);
+      if (set?.empty()) {
+        this.buckets.delete(band);
+      }
+    }
+  }
+
+  private signature(value: string): Uint32Array {
+    const shingles = new Set<string>();
+    for (let i = 0; i + this.shingleSize <= value.length; i++) {
+      shingles.add(value.slice(i, i + this.shingleSize));
+    }
+    if (shingles.size === 0) {
+      shingles.add(value);
+    }
+
+    const ret = new Uint32Array(this.numHashes).fill(0xffffffff);
+    for (const shingle of shingles) {
+      for (let i = 0; i < this.numHashes; i++) {
+        const h = fnv1a(shingle, (0x811c9dc5 ^ Math.imul(i, 0x9e3779b1)) >>> 0);
+        if (h < ret[i]) ret[i] = h;
+      }
+    }
+    return ret;
+  }
+
+  private bands(signature: Uint32Array): string[] {
+    const ret: string[] = [];
+    for (let start = 0; start + this.bandSize <= signature.length; start += this.bandSize) {
+      ret.push(`${start}:${signature.subarray(start, start + this.bandSize).join(",")}`);
+    }
+    return ret;
+  }
+
+  private candidates(signature: Uint32Array): IdSet {
+    const ret = new IdSet();
+    for (const band of this.bands(signature)) {
+      this.buckets.get(band)?.forEach((id) => ret.set(id));
+    }
+    return ret;
+  }
+
+  // Queries
+
+  /**
+   * Items similar to the given one, most similar first, excluding the item
+   * itself.
+   *
+   * Complexity: `O(numHashes * c * log(c))` where `c` is the number of
+   * candidates
+   */
+  nearDuplicatesOf(id: Id, minSimilarity: number = 0): Similar<Out>[] {
+    const signature = this.signatures.get(id);
+    if (!signature) return [];
+    return this.similar(signature, minSimilarity, id);
+  }
+
+  /**
+   * Items similar to the given string, most similar first.
+   *
+   * Complexity: `O(numHashes * (s + c * log(c)))` where `s` is the length of
+   * the string and `c` is the number of candidates
+   */
+  similarTo(value: string, minSimilarity: number = 0): Similar<Out>[] {
+    return this.similar(this.signature(value), minSimilarity, undefined);
+  }
+
+  /**
+   * Every pair of items sharing a band, each pair returned once. These are
+   * the pairs worth comparing exactly when looking for all near-duplicates.
+   *
+   * Complexity: `O(p)` where `p` is the number of pairs, summed over the
+   * bands
+   */
+  candidatePairs(): [Id, Id][] {
+    const seen = new Set<string>();
+    const ret: [Id, Id][] = [];
+    for (const set of this.buckets.values()) {
+      const ids = Array.from(set.values());
+      for (let i = 0; i < ids.length; i++) {
+        for (let j = i + 1; j < ids.length; j++) {
+          const [a, b] = ids[i].compare(ids[j]) < 0 ? [ids[i], ids[j]] : [ids[j], ids[i]];
+          const key = `${a}:${b}`;
+          if (!seen.has(key)) {
+            seen.add(key);
+            ret.push([a, b]);
+          }
+        }
+      }
+    }
+    return ret;
+  }
+
+  /**
+   * The estimated similarity of two items, or `undefined` if either is
+   * missing.
+   *
+   * Complexity: `O(numHashes)`
+   */
+  similarity(a: Id, b: Id): number | undefined {
+    const sa = this.signatures.get(a);
+    const sb = this.signatures.get(b);
+    if (!sa || !sb) return undefined;
+    return estimate(sa, sb);
+  }
+
+  private similar(
+    signature: Uint32Array,
+    minSimilarity: number,
+    exclude: Id | undefined
+  ): Similar<Out>[] {
+    const ret: Similar<Out>[] = [];
+    this.candidates(signature).forEach((id) => {
+      if (exclude?.equals(id)) return;
+      const similarity = estimate(signature, this.signatures.get(id)!);
+      if (similarity >= minSimilarity) {
+        ret.push({ item: this.item(id), similarity });
+      }
+    });
+    ret.sort((a, b) => b.similarity - a.similarity || a.item.id.compare(b.item.id));
+    return ret;
+  }
+}
+
+function estimate(a: Uint32Array, b: Uint32Array): number {
+  let equal = 0;
+  for (let i = 0; i < a.length; i++) {
+    if (a[i] === b[i]) equal++;
+  }
+  return equal / a.length;
+}
+
+/**
+ * Create a new {@link MinHashIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixBody = collection.registerIndex(
+ *   premap((doc) => doc.body, minHashIndex({ numHashes: 64, bandSize: 4 }))
+ * );
+ *
+ * const dupes = ixBody.get.nearDuplicatesOf(id, 0.8);
+ * ```
+ *
+ * @param args.numHashes The length of the signatures. More hashes give more
+ * accurate similarities, at the cost of memory and time.
+ * @param args.bandSize The number of hashes per band. Should divide
+ * `numHashes`; leftover hashes are only used for the similarity estimates.
+ * @param args.shingleSize The length of the character n-grams. Defaults to 3.
+ */
+export function minHashIndex<Out>(args: {
+  numHashes: number;
+  bandSize: number;
+  shingleSize?: number;
+}): UnregisteredIndex<string, Out, MinHashIndex<Out>> {
+  return MinHashIndex.create({
+    numHashes: args.numHashes,
+    bandSize: args.bandSize,
+    shingleSize: args.shingleSize ?? 3,
+  });
+}
@@ -0,0 +1,55 @@
+import test from "node:test";
+import { minMaxIndex } from "./MinMaxIndex";
+import { AggregateIndex } from "./AggregateIndex";
+import fc from "fast-check";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { testProps } from "../test_util/invariants";
+import { Collection } from "../core/Collection";
+import { strict as assert } from "node:assert";
+
+type Ix = AggregateIndex<number, { min: number; max: number } | undefined>;
+
+test("MinMaxIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        Ix,
+        { min: number; max: number } | undefined
+      >({
+        valueGen: fc.integer({ min: 0, max: 20 }),
+        index: minMaxIndex(),
+        value: (ix) => ix.value(),
+        reference: (arr) => {
+          const values = arr.map((it) => it.value);
+          if (values.length === 0) return undefined;
+          return { min: Math.min(...values), max: Math.max(...values) };
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("churn", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(minMaxIndex());
+    const ids = c.addAll([0, 50, 100]);
+
+    for (let i = 0; i < 10000; i++) {
+      c.set(ids[1], 1 + (i % 98));
+    }
+
+    assert.deepEqual(ix.value(), { min: 0, max: 100 });
+    assert.deepEqual(ix.describe().parameters, { distinctValues: 3 });
+  });
+
+  await test("props", async (t) => {
+    await testProps<number, Ix, { min: number; max: number } | undefined>(t, {
+      valueGen: fc.integer(),
+      index: minMaxIndex(),
+      witness: (ix) => ix.value(),
+    });
+  });
+});
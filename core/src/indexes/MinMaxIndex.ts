@@ -0,0 +1,98 @@
+import { IndexContext, IndexDescription } from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+import { AggregateIndex, UnregisteredAggregateIndex } from "./AggregateIndex";
+import BTree from "sorted-btree";
+
+/**
+ * An aggregate index for the smallest and the largest value.
+ *
+ * Keeps the number of items of each distinct value in a `BTree`. Lighter
+ * than a {@link BTreeIndex} when only the bounds are needed, for example per
+ * group, since it does not track the items themselves.
+ *
+ * `O(log(d))` query and update, and `O(d)` memory complexity, where `d` is
+ * the number of distinct values.
+ *
+ * @see {@link minMaxIndex} as a constructor.
+ */
+export class MinMaxIndex extends AggregateIndex<
+  number,
+  { min: number; max: number } | undefined
+> {
+  private readonly counts = new BTree<number, number>();
+
+  private constructor(ctx: IndexContext<any>) {
+    super(ctx);
+  }
+
+  static create(): UnregisteredAggregateIndex<
+    number,
+    { min: number; max: number } | undefined
+  > {
+    return new UnregisteredAggregateIndex((ctx) => new MinMaxIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<number>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.oldValue);
+        this.add(update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(value: number): void {
+    this.counts.set(value, (this.counts.get(value) ?? 0) + 1);
+  }
+
+  private delete(value: number): void {
+    const count = this.counts.get(value);
+    if (count === undefined) {
+      this.inconsistent(`Deleted value ${value} is not indexed`);
+    } else if (count === 1) {
+      this.counts.delete(value);
+    } else {
+      this.counts.set(value, count - 1);
+    }
+  }
+
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters: { distinctValues: this.counts.size },
+    };
+  }
+
+  override value(): { min: number; max: number } | undefined {
+    const min = this.counts.minKey();
+    if (min === undefined) return undefined;
+    return { min, max: this.counts.maxKey()! };
+  }
+}
+
+/**
+ * Create a new {@link MinMaxIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixPriceRange = collection.registerIndex(
+ *   group((p) => p.category, minMaxIndex().premap((p) => p.price))
+ * );
+ *
+ * console.log(ixPriceRange.get("books")?.value());
+ * ```
+ */
+export function minMaxIndex(): UnregisteredAggregateIndex<
+  number,
+  { min: number; max: number } | undefined
+> {
+  return MinMaxIndex.create();
+}
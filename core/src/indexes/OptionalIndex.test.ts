@@ -0,0 +1,60 @@
+import test from "node:test";
+import fc from "fast-check";
+import { OptionalIndex, optional } from "./OptionalIndex";
+import { HashIndex, hashIndex } from "./HashIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+type Person = { middleName: number | undefined };
+
+test("OptionalIndex", async () => {
+  const valueGen = fc.record({
+    middleName: fc.option(fc.nat({ max: 5 }), { nil: undefined }),
+  });
+
+  await test("OptionalIndex.ref.none", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        Person,
+        OptionalIndex<Person, Person, number, HashIndex<number, Person>>,
+        [number, string[]]
+      >({
+        valueGen,
+        index: optional((p) => p.middleName, hashIndex()),
+        value: (ix) => [
+          ix.noneCount(),
+          ix.noneItems().map((i) => i.id.toString()).sort(),
+        ],
+        reference: (arr) => {
+          const none = arr.filter((i) => i.value.middleName === undefined);
+          return [none.length, none.map((i) => i.id.toString()).sort()];
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("OptionalIndex.ref.inner", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        Person,
+        OptionalIndex<Person, Person, number, HashIndex<number, Person>>,
+        number
+      >({
+        valueGen,
+        index: optional((p) => p.middleName, hashIndex()),
+        value: (ix) => ix.get.countDistinct(),
+        reference: (arr) =>
+          new Set(
+            arr
+              .map((i) => i.value.middleName)
+              .filter((v) => v !== undefined)
+          ).size,
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
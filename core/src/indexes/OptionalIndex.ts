@@ -0,0 +1,126 @@
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType, filterMapUpdate } from "../core/Update";
+import { Id, Item } from "../core/simple_types";
+import { IdSet, unreachable } from "../util";
+
+/**
+ * Like {@link premap}, passes inputs through a function before handing them
+ * to the given index; but also keeps track of the items for which the
+ * function returns `undefined`.
+ *
+ * Memory footprint: `O(k)` on top of the inner index, where `k` is the
+ * number of items without a value
+ *
+ * @see {@link optional} as a constructor.
+ */
+export class OptionalIndex<
+  In,
+  Out,
+  InnerIn,
+  Inner extends Index<InnerIn, Out>
+> extends Index<In, Out> {
+  private readonly none = new IdSet();
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly inner: Inner,
+    private readonly f: (_: In) => InnerIn | undefined
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Out, InnerIn, Inner extends Index<InnerIn, Out>>(
+    f: (_: In) => InnerIn | undefined,
+    inner: UnregisteredIndex<InnerIn, Out, Inner>
+  ): UnregisteredIndex<In, Out, OptionalIndex<In, Out, InnerIn, Inner>> {
+    return new UnregisteredIndex(
+      (ctx) => new OptionalIndex(ctx, inner._register(ctx), f)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    const innerUpdate = filterMapUpdate(this.f, update);
+    const hook = innerUpdate ? this.inner._onUpdate(innerUpdate) : () => {};
+
+    return () => {
+      hook();
+      if (update.type === UpdateType.ADD) {
+        this.track(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.none.delete(update.id);
+        this.track(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.none.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private track(id: Id, value: In): void {
+    if (this.f(value) === undefined) {
+      this.none.set(id);
+    }
+  }
+
+  // Queries
+
+  /**
+   * The items without a value.
+   *
+   * Complexity: `O(k)` where `k` is the number of items fetched
+   */
+  noneItems(): Item<Out>[] {
+    const ret: Item<Out>[] = [];
+    this.none.forEach((id) => {
+      ret.push(this.item(id));
+    });
+    return ret;
+  }
+
+  /**
+   * The number of items without a value.
+   *
+   * Complexity: `O(1)`
+   */
+  noneCount(): number {
+    return this.none.size;
+  }
+
+  override describe(): IndexDescription {
+    return { ...super.describe(), children: [this.inner.describe()] };
+  }
+
+  /**
+   * The index over the items with a value.
+   */
+  get get(): Inner {
+    return this.inner;
+  }
+}
+
+/**
+ * Create a new {@link OptionalIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixMiddleNames = collection.registerIndex(
+ *   optional((p) => p.middleName, hashIndex())
+ * );
+ *
+ * const withoutMiddleName = ixMiddleNames.noneItems();
+ * const johns = ixMiddleNames.get.eq("John");
+ * ```
+ */
+export function optional<In, Out, InnerIn, Inner extends Index<InnerIn, Out>>(
+  f: (_: In) => InnerIn | undefined,
+  inner: UnregisteredIndex<InnerIn, Out, Inner>
+): UnregisteredIndex<In, Out, OptionalIndex<In, Out, InnerIn, Inner>> {
+  return OptionalIndex.create(f, inner);
+}
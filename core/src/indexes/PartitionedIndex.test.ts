@@ -0,0 +1,77 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { PartitionedIndex, partitioned } from "./PartitionedIndex";
+import { HashIndex, hashIndex } from "./HashIndex";
+import { Collection } from "../core/Collection";
+import { premap } from "../core/Index";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+test("PartitionedIndex", async () => {
+  await test("PartitionedIndex.ref.sizes", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        PartitionedIndex<number, number, number, HashIndex<number, number>>,
+        [number, number][]
+      >({
+        valueGen: fc.nat({ max: 20 }),
+        index: partitioned((i) => i % 3, hashIndex()),
+        value: (ix) => Array.from(ix.sizes()).sort(),
+        reference: (arr) => {
+          const sizes = new Map<number, number>();
+          for (const it of arr) {
+            sizes.set(it.value % 3, (sizes.get(it.value % 3) ?? 0) + 1);
+          }
+          return Array.from(sizes).sort();
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("PartitionedIndex.ref.inner", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        PartitionedIndex<number, number, number, HashIndex<number, number>>,
+        number
+      >({
+        valueGen: fc.nat({ max: 20 }),
+        index: partitioned((i) => i % 3, hashIndex()),
+        value: (ix) => ix.get(1)?.countDistinct() ?? 0,
+        reference: (arr) =>
+          new Set(arr.map((i) => i.value).filter((v) => v % 3 === 1)).size,
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("drop partition", () => {
+    type User = { tenant: string; email: string };
+    const c = new Collection<User>();
+    const ix = c.registerIndex(
+      partitioned(
+        (u: User) => u.tenant,
+        premap((u: User) => u.email, hashIndex<string, User>())
+      )
+    );
+
+    c.add({ tenant: "acme", email: "bob@acme.com" });
+    c.add({ tenant: "acme", email: "alice@acme.com" });
+    const other = c.add({ tenant: "initech", email: "peter@initech.com" });
+
+    assert.strictEqual(ix.size("acme"), 2);
+    assert.strictEqual(ix.get("acme")?.get.eq("bob@acme.com").length, 1);
+    assert.strictEqual(c.deletePartition(ix, "acme").length, 2);
+
+    assert.strictEqual(ix.get("acme"), undefined);
+    assert.strictEqual(ix.size("acme"), 0);
+    assert.deepEqual(ix.ids("initech"), [other]);
+    assert.deepEqual(Array.from(ix.sizes()), [["initech", 1]]);
+  });
+});
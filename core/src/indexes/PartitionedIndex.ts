@@ -0,0 +1,282 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import {
+  AddUpdate,
+  DeleteUpdate,
+  Update,
+  UpdateType,
+  UpdateUpdate,
+} from "../core/Update";
+import { IdSet, unreachable } from "../util";
+
+type Partition<Inner> = {
+  readonly ids: IdSet;
+  readonly ix: Inner;
+};
+
+/**
+ * Splits the items into partitions by a key, and maintains a separate index
+ * for each partition. Unlike {@link GroupedIndex}, it keeps the ids of each
+ * partition, so that a whole partition can be deleted with
+ * {@link Collection.deletePartition}, which throws away the index of the
+ * partition without updating it item by item; and it reports the sizes of
+ * all the partitions at once with {@link PartitionedIndex.sizes}.
+ *
+ * This is meant for multi-tenant data, where the partition key is the
+ * tenant.
+ *
+ * Memory footprint: `O(n)` on top of the inner indexes
+ *
+ * @see {@link partitioned} as a constructor.
+ */
+export class PartitionedIndex<
+  In,
+  Out,
+  Key extends string | number,
+  Inner extends Index<In, Out>
+> extends Index<In, Out> {
+  private readonly partitions: Map<Key, Partition<Inner>> = new Map();
+
+  private constructor(
+    private readonly ctx: IndexContext<Out>,
+    private readonly inner: UnregisteredIndex<In, Out, Inner>,
+    private readonly key: (_: In) => Key
+  ) {
+    super(ctx);
+  }
+
+  static create<
+    In,
+    Out,
+    Key extends string | number,
+    Inner extends Index<In, Out>
+  >(
+    key: (_: In) => Key,
+    inner: UnregisteredIndex<In, Out, Inner>
+  ): UnregisteredIndex<In, Out, PartitionedIndex<In, Out, Key, Inner>> {
+    return new UnregisteredIndex(
+      (ctx) => new PartitionedIndex(ctx, inner, key)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    if (update.type === UpdateType.ADD) {
+      return this.add(update);
+    } else if (update.type === UpdateType.UPDATE) {
+      return this.update(update);
+    } else if (update.type === UpdateType.DELETE) {
+      return this.delete(update);
+    } else {
+      unreachable(update);
+    }
+  }
+
+  /** @internal */
+  override _onUpdates(updates: Update<In>[]): () => void {
+    if (!updates.every((u): u is DeleteUpdate<In> => u.type === UpdateType.DELETE)) {
+      return super._onUpdates(updates);
+    }
+
+    const byKey: Map<Key, DeleteUpdate<In>[]> = new Map();
+    for (const update of updates) {
+      const key = this.key(update.oldValue);
+      const batch = byKey.get(key);
+      if (batch) {
+        batch.push(update);
+      } else {
+        byKey.set(key, [update]);
+      }
+    }
+
+    const hooks: (() => void)[] = [];
+    for (const [key, batch] of byKey) {
+      const partition = this.partitions.get(key);
+      const present = batch.filter((u) => partition?.ids.has(u.id));
+      if (present.length < batch.length) {
+        this.inconsistent(`Deleted items are not in partition ${key}`);
+      }
+      if (!partition || present.length === 0) {
+        continue;
+      }
+
+      if (present.length === partition.ids.size) {
+        // The whole partition goes, so its index can be dropped as is.
+        hooks.push(() => {
+          this.partitions.delete(key);
+        });
+        continue;
+      }
+
+      const hook = partition.ix._onUpdates(present);
+      hooks.push(() => {
+        hook();
+        this.release(key, partition, present);
+      });
+    }
+
+    return () => {
+      for (const hook of hooks) {
+        hook();
+      }
+    };
+  }
+
+  private add(update: AddUpdate<In>): () => void {
+    const key = this.key(update.value);
+    // A new partition is only stored once the inner index accepts the item,
+    // so that a rejected item does not leave an empty partition behind.
+    const partition = this.partitions.get(key) ?? {
+      ids: new IdSet(),
+      ix: this.inner._register(this.ctx),
+    };
+    const hook = partition.ix._onUpdate(update);
+    return () => {
+      hook();
+      partition.ids.set(update.id);
+      this.partitions.set(key, partition);
+    };
+  }
+
+  private update(update: UpdateUpdate<In>): () => void {
+    const oldKey = this.key(update.oldValue);
+    const newKey = this.key(update.newValue);
+
+    if (oldKey !== newKey) {
+      const deleteHook = this.delete({
+        type: UpdateType.DELETE,
+        id: update.id,
+        oldValue: update.oldValue,
+      });
+      const addHook = this.add({
+        type: UpdateType.ADD,
+        id: update.id,
+        value: update.newValue,
+      });
+      return () => {
+        deleteHook();
+        addHook();
+      };
+    }
+
+    const partition = this.partitions.get(oldKey);
+    if (!partition?.ids.has(update.id)) {
+      this.inconsistent(`Updated item ${update.id} is not in partition ${oldKey}`);
+      return this.add({
+        type: UpdateType.ADD,
+        id: update.id,
+        value: update.newValue,
+      });
+    }
+    return partition.ix._onUpdate(update);
+  }
+
+  private delete(update: DeleteUpdate<In>): () => void {
+    const key = this.key(update.oldValue);
+    const partition = this.partitions.get(key);
+    if (!partition?.ids.has(update.id)) {
+      this.inconsistent(`Deleted item ${update.id} is not in partition ${key}`);
+      return () => {};
+    }
+
+    const hook = partition.ix._onUpdate(update);
+    return () => {
+      hook();
+      this.release(key, partition, [update]);
+    };
+  }
+
+  private release(
+    key: Key,
+    partition: Partition<Inner>,
+    updates: DeleteUpdate<In>[]
+  ): void {
+    for (const update of updates) {
+      partition.ids.delete(update.id);
+    }
+    if (partition.ids.empty()) {
+      this.partitions.delete(key);
+    }
+  }
+
+  // Queries
+
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters: { partitions: this.partitions.size },
+      children: [this.inner._register(this.ctx).describe()],
+    };
+  }
+
+  /**
+   * The index of the given partition, or `undefined` if it has no items.
+   *
+   * Complexity: `O(1)`
+   */
+  get(key: Key): Inner | undefined {
+    return this.partitions.get(key)?.ix;
+  }
+
+  /**
+   * The ids of the items in the given partition.
+   *
+   * Complexity: `O(m)` where `m` is the size of the partition
+   */
+  ids(key: Key): Id[] {
+    const partition = this.partitions.get(key);
+    return partition ? Array.from(partition.ids.values()) : [];
+  }
+
+  /**
+   * The number of items in the given partition.
+   *
+   * Complexity: `O(1)`
+   */
+  size(key: Key): number {
+    return this.partitions.get(key)?.ids.size ?? 0;
+  }
+
+  /**
+   * The number of items in each non-empty partition.
+   *
+   * Complexity: `O(p)` where `p` is the number of partitions
+   */
+  sizes(): Map<Key, number> {
+    const ret: Map<Key, number> = new Map();
+    for (const [key, partition] of this.partitions) {
+      ret.set(key, partition.ids.size);
+    }
+    return ret;
+  }
+}
+
+/**
+ * Create a new {@link PartitionedIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixByTenant = collection.registerIndex(
+ *   partitioned((u) => u.tenant, premap((u) => u.email, uniqueHashIndex()))
+ * );
+ *
+ * const bob = ixByTenant.get("acme")?.get.eq("bob@acme.com");
+ * console.log(ixByTenant.size("acme"));
+ * ```
+ */
+export function partitioned<
+  In,
+  Out,
+  Key extends string | number,
+  Inner extends Index<In, Out>
+>(
+  key: (_: In) => Key,
+  inner: UnregisteredIndex<In, Out, Inner>
+): UnregisteredIndex<In, Out, PartitionedIndex<In, Out, Key, Inner>> {
+  return PartitionedIndex.create(key, inner);
+}
@@ -0,0 +1,75 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { project } from "./ProjectionIndex";
+import { sumIndex } from "./FoldIndex";
+import { Collection, ConflictException } from "../core/Collection";
+import { uniqueHashIndex } from "./UniqueHashIndex";
+import { arbCalls, playCalls } from "../test_util/call";
+
+test("ProjectionIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      fc.property(
+        arbCalls({ value: fc.integer({ min: 0, max: 20 }), idRange: 10, maxLength: 100 }),
+        (calls) => {
+          const source = new Collection<number>();
+          const evens = project(source, (v) => (v % 2 === 0 ? v * 10 : undefined));
+          const sum = evens.registerIndex(sumIndex());
+          playCalls(source, calls);
+
+          const expected = source
+            .toList()
+            .filter(([, v]) => v % 2 === 0)
+            .map(([id, v]) => [id.toString(), v * 10]);
+          assert.deepEqual(
+            evens.toList().map(([id, v]) => [id.toString(), v]).sort(),
+            expected.sort()
+          );
+          assert.strictEqual(
+            sum.value(),
+            expected.reduce((acc, [, v]) => acc + (v as number), 0)
+          );
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("existing items", () => {
+    const source = Collection.from([1, 2, 3]);
+    const doubled = project(source, (v) => v * 2);
+    assert.deepEqual(
+      doubled.toList().map(([, v]) => v),
+      [2, 4, 6]
+    );
+  });
+
+  await test("rejecting target", () => {
+    const source = new Collection<number>();
+    const sum = source.registerIndex(sumIndex());
+    const parities = project(source, (v) => v % 2);
+    parities.registerIndex(uniqueHashIndex());
+    const one = source.add(1);
+    source.add(2);
+
+    const version = source.version;
+    assert.throws(() => source.add(3), ConflictException);
+    assert.throws(() => source.set(one, 4), ConflictException);
+    parities.onRemove(() => {
+      throw new Error("vetoed");
+    });
+    assert.throws(() => source.delete(one), /vetoed/);
+
+    assert.deepEqual(source.toList().map(([, v]) => v), [1, 2]);
+    assert.deepEqual(parities.toList().map(([, v]) => v), [1, 0]);
+    assert.strictEqual(sum.value(), 3);
+    assert.strictEqual(source.version, version);
+
+    // The source stays usable.
+    source.set(one, 7);
+    assert.strictEqual(sum.value(), 9);
+  });
+});
@@ -0,0 +1,91 @@
+import { Id } from "..";
+import { Collection } from "../core/Collection";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+
+/**
+ * Keeps another collection in sync with the items of this one, passed
+ * through a function. Items for which the function returns `undefined` are
+ * left out. Projected items keep their {@link Id}s.
+ *
+ * Writes to the target collection are validated along with the update of
+ * the source, so if an index or a hook of the target rejects a projected
+ * item, neither collection changes.
+ *
+ * @see {@link project} as a constructor.
+ */
+export class ProjectionIndex<In, Out, U> extends Index<In, Out> {
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly target: Collection<U>,
+    private readonly f: (value: In, id: Id) => U | undefined
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Out, U>(
+    target: Collection<U>,
+    f: (value: In, id: Id) => U | undefined
+  ): UnregisteredIndex<In, Out, ProjectionIndex<In, Out, U>> {
+    return new UnregisteredIndex(
+      (ctx) => new ProjectionIndex(ctx, target, f)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    if (update.type === UpdateType.ADD) {
+      return this.write(update.id, update.value);
+    } else if (update.type === UpdateType.UPDATE) {
+      return this.write(update.id, update.newValue);
+    } else if (update.type === UpdateType.DELETE) {
+      return this.target._prepareDelete(update.id);
+    } else {
+      unreachable(update);
+    }
+  }
+
+  private write(id: Id, value: In): () => void {
+    const projected = this.f(value, id);
+    if (projected === undefined) {
+      return this.target._prepareDelete(id);
+    } else {
+      return this.target._prepareSet(id, projected);
+    }
+  }
+
+  /**
+   * The collection kept in sync.
+   */
+  get get(): Collection<U> {
+    return this.target;
+  }
+}
+
+/**
+ * Creates a collection that is kept in sync with `source`, holding the
+ * results of `f` on its items; like a materialized view. Items for which `f`
+ * returns `undefined` are left out. Register indexes on the returned
+ * collection to query the view.
+ *
+ * @example
+ * ```typescript
+ * const activeUsers = project(users, (u) => (u.active ? u : undefined));
+ * const ixActiveByCountry = activeUsers.registerIndex(
+ *   premap((u) => u.country, hashIndex())
+ * );
+ * ```
+ */
+export function project<T, U>(
+  source: Collection<T>,
+  f: (value: T, id: Id) => U | undefined
+): Collection<U> {
+  const ret = new Collection<U>();
+  source.registerIndex(ProjectionIndex.create<T, T, U>(ret, f));
+  return ret;
+}
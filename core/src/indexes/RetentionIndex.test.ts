@@ -0,0 +1,76 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { RetentionIndex, retentionIndex } from "./RetentionIndex";
+import { premap } from "../core/Index";
+import { Collection } from "../core/Collection";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+type Event = { at: number; body: string };
+
+test("RetentionIndex", async () => {
+  await test("ref", () => {
+    const now = 20;
+    fc.assert(
+      propIndexAgainstReference<number, RetentionIndex<number>, number[]>({
+        valueGen: fc.nat({ max: 20 }),
+        index: retentionIndex({
+          maxAge: 12,
+          maxCount: 5,
+          maxSize: 40,
+          size: (v) => v,
+        }),
+        value: (ix) =>
+          ix
+            .expired(now)
+            .map((item) => item.value)
+            .sort((a, b) => a - b),
+        reference: (arr) => {
+          const values = arr.map((i) => i.value).sort((a, b) => a - b);
+          let size = values.reduce((a, b) => a + b, 0);
+          let n = 0;
+          while (
+            n < values.length &&
+            (values[n] < now - 12 || values.length - n > 5 || size > 40)
+          ) {
+            size -= values[n];
+            n++;
+          }
+          return values.slice(0, n);
+        },
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("enforce", () => {
+    const c = new Collection<Event>();
+    const retention = c.registerIndex(
+      premap((e: Event) => e.at, retentionIndex<Event>({ maxAge: 10, maxCount: 2 }))
+    );
+
+    c.add({ at: 1, body: "a" });
+    c.add({ at: 5, body: "b" });
+    c.add({ at: 8, body: "c" });
+    c.add({ at: 9, body: "d" });
+
+    assert.deepEqual(
+      retention.get.enforce(c, 12).map((e) => e.body),
+      ["a", "b"]
+    );
+    assert.deepEqual(
+      retention.get.enforce(c, 19).map((e) => e.body),
+      ["c"]
+    );
+    assert.deepEqual(retention.get.enforce(c, 19), []);
+    const remaining: string[] = [];
+    c.forEach((e) => remaining.push(e.body));
+    assert.deepEqual(remaining, ["d"]);
+  });
+
+  await test("maxSize requires size", () => {
+    assert.throws(() => retentionIndex({ maxSize: 10 }));
+  });
+});
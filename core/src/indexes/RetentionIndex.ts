@@ -0,0 +1,193 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import type { Collection } from "../core/Collection";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdMap, IdSet, unreachable } from "../util";
+import BTree from "sorted-btree";
+
+/**
+ * Limits on the items a {@link RetentionIndex} keeps. A missing limit is not
+ * enforced.
+ */
+export type RetentionPolicy<Out> = {
+  /** Items older than this are expired, in the unit of the timestamps. */
+  maxAge?: number;
+  /** At most this many of the newest items are kept. */
+  maxCount?: number;
+  /** The newest items are kept as long as their total `size` fits. */
+  maxSize?: number;
+  /** The size of an item for `maxSize`, for example an estimate in bytes. */
+  size?: (value: Out) => number;
+};
+
+/**
+ * Indexes items by a timestamp, to find the ones a {@link RetentionPolicy}
+ * expires. Nothing is deleted until {@link RetentionIndex.enforce} is called,
+ * so the caller decides when, and what `now` is.
+ *
+ * Memory footprint: `O(n)`
+ *
+ * @see {@link retentionIndex} as a constructor.
+ */
+export class RetentionIndex<Out> extends Index<number, Out> {
+  private readonly ix = new BTree<number, IdSet>();
+  private readonly sizes: IdMap<number> = new IdMap();
+  private count = 0;
+  private totalSize = 0;
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly policy: RetentionPolicy<Out>
+  ) {
+    super(ctx);
+  }
+
+  static create<Out>(
+    policy: RetentionPolicy<Out>
+  ): UnregisteredIndex<number, Out, RetentionIndex<Out>> {
+    if (policy.maxSize !== undefined && policy.size === undefined) {
+      throw new Error("composable-indexes: maxSize requires a size function");
+    }
+    return new UnregisteredIndex((ctx) => new RetentionIndex(ctx, policy));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<number>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.delete(update.id, update.oldValue);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id, update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, timestamp: number): void {
+    const set = this.ix.get(timestamp);
+    if (set !== undefined) {
+      set.set(id);
+    } else {
+      this.ix.set(timestamp, IdSet.singleton(id));
+    }
+    this.count++;
+
+    if (this.policy.size) {
+      const size = this.policy.size(this.item(id).value);
+      this.sizes.set(id, size);
+      this.totalSize += size;
+    }
+  }
+
+  private delete(id: Id, timestamp: number): void {
+    const set = this.ix.get(timestamp);
+    if (!set?.has(id)) {
+      this.inconsistent(`Deleted item ${id} is not indexed under ${timestamp}`);
+      return;
+    }
+    set.delete(id);
+    if (set.empty()) {
+      this.ix.delete(timestamp);
+    }
+    this.count--;
+
+    const size = this.sizes.get(id);
+    if (size !== undefined) {
+      this.sizes.delete(id);
+      this.totalSize -= size;
+    }
+  }
+
+  // Queries
+
+  override describe(): IndexDescription {
+    return {
+      ...super.describe(),
+      parameters: { count: this.count, totalSize: this.totalSize },
+    };
+  }
+
+  /**
+   * The total size of the items, or `0` without a `size` function.
+   */
+  size(): number {
+    return this.totalSize;
+  }
+
+  /**
+   * The items the policy expires at `now`, oldest first. An item is expired
+   * if it is older than `maxAge`, or if keeping it would exceed `maxCount` or
+   * `maxSize` along with all the newer items.
+   *
+   * Complexity: `O(log(n) + m)` where `m` is the number of expired items
+   */
+  expired(now: number): Item<Out>[] {
+    const { maxAge, maxCount, maxSize } = this.policy;
+    const ret: Item<Out>[] = [];
+    let count = this.count;
+    let size = this.totalSize;
+
+    for (const [timestamp, set] of this.ix.entries()) {
+      for (const id of set.values()) {
+        const expired =
+          (maxAge !== undefined && timestamp < now - maxAge) ||
+          (maxCount !== undefined && count > maxCount) ||
+          (maxSize !== undefined && size > maxSize);
+        if (!expired) return ret;
+
+        ret.push(this.item(id));
+        count--;
+        size -= this.sizes.get(id) ?? 0;
+      }
+    }
+    return ret;
+  }
+
+  /**
+   * Deletes the expired items from the collection the index is registered
+   * to, through {@link Collection.deleteMany}, so that the other indexes and
+   * the remove hooks see the deletions.
+   *
+   * @returns The deleted values, oldest first.
+   */
+  enforce(collection: Collection<Out>, now: number): Out[] {
+    return collection.deleteMany(this.expired(now).map((item) => item.id));
+  }
+}
+
+/**
+ * Constructs a {@link RetentionIndex}. Use it with {@link premap} to pick the
+ * timestamp of the items.
+ *
+ * @example
+ * ```typescript
+ * const retention = collection.registerIndex(
+ *   premap(
+ *     (e) => e.receivedAt,
+ *     retentionIndex({
+ *       maxAge: 60 * 60 * 1000,
+ *       maxCount: 10_000,
+ *       maxSize: 16 * 1024 * 1024,
+ *       size: (e) => e.body.length,
+ *     })
+ *   )
+ * );
+ *
+ * setInterval(() => retention.get.enforce(collection, Date.now()), 1000);
+ * ```
+ */
+export function retentionIndex<Out>(
+  policy: RetentionPolicy<Out>
+): UnregisteredIndex<number, Out, RetentionIndex<Out>> {
+  return RetentionIndex.create(policy);
+}
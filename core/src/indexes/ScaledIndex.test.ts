@@ -0,0 +1,59 @@
+import test from "node:test";
+import fc from "fast-check";
+import { strict as assert } from "node:assert";
+import { ScaledIndex, quantized, scaled } from "./ScaledIndex";
+import { BTreeIndex, btreeIndex } from "./BTreeIndex";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+test("ScaledIndex", async () => {
+  await test("scaled", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        ScaledIndex<number, BTreeIndex<number, number>>,
+        [number, number | undefined]
+      >({
+        valueGen: fc.integer({ min: 0, max: 500 }).map((n) => n / 100),
+        index: scaled(100, btreeIndex()),
+        value: (ix) => {
+          const max = ix.get.max1();
+          return [
+            ix.get.eq(ix.toInner(0.3)).length,
+            max && ix.fromInner(ix.toInner(max.value)),
+          ];
+        },
+        reference: (arr) => [
+          arr.filter((i) => Math.round(i.value * 100) === 30).length,
+          arr.length === 0 ? undefined : Math.max(...arr.map((i) => i.value)),
+        ],
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("quantized", () => {
+    fc.assert(
+      propIndexAgainstReference<
+        number,
+        ScaledIndex<number, BTreeIndex<number, number>>,
+        number
+      >({
+        valueGen: fc.integer({ min: -50, max: 50 }),
+        index: quantized(10, btreeIndex()),
+        value: (ix) => ix.get.eq(ix.toInner(-3)).length,
+        reference: (arr) => arr.filter((i) => i.value >= -10 && i.value < 0).length,
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("quantized.invalid step", () => {
+    for (const step of [0, -60, NaN]) {
+      assert.throws(() => quantized(step, btreeIndex()), /composable-indexes: step/);
+    }
+  });
+});
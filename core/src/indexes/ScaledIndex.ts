@@ -0,0 +1,122 @@
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, mapUpdate } from "../core/Update";
+
+/**
+ * Passes numbers through a conversion before handing them to the inner
+ * index, and keeps the conversion at hand to translate query arguments and
+ * results. Meant for money and time fields, where the indexed unit differs
+ * from the stored one.
+ *
+ * @see {@link scaled} and {@link quantized} as constructors.
+ */
+export class ScaledIndex<Out, Inner extends Index<number, Out>> extends Index<
+  number,
+  Out
+> {
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly inner: Inner,
+    private readonly to: (value: number) => number,
+    private readonly from: (value: number) => number
+  ) {
+    super(ctx);
+  }
+
+  static create<Out, Inner extends Index<number, Out>>(
+    inner: UnregisteredIndex<number, Out, Inner>,
+    to: (value: number) => number,
+    from: (value: number) => number
+  ): UnregisteredIndex<number, Out, ScaledIndex<Out, Inner>> {
+    return new UnregisteredIndex(
+      (ctx) => new ScaledIndex(ctx, inner._register(ctx), to, from)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<number>): () => void {
+    return this.inner._onUpdate(mapUpdate(this.to, update));
+  }
+
+  /**
+   * Converts a value to the unit of the inner index, the same way the
+   * indexed values are; for example to build a query argument.
+   */
+  toInner(value: number): number {
+    return this.to(value);
+  }
+
+  /**
+   * Converts a value of the inner index back, for example a query result.
+   */
+  fromInner(value: number): number {
+    return this.from(value);
+  }
+
+  override describe(): IndexDescription {
+    return { ...super.describe(), children: [this.inner.describe()] };
+  }
+
+  get get(): Inner {
+    return this.inner;
+  }
+}
+
+/**
+ * Multiplies the values by `factor`, rounding to the nearest integer, before
+ * indexing them. For example `scaled(100, ...)` indexes dollar amounts as
+ * whole cents, so that equality queries are not thrown off by floating-point
+ * error.
+ *
+ * @example
+ * ```typescript
+ * const ixPrice = collection.registerIndex(
+ *   premap((p) => p.price, scaled(100, btreeIndex()))
+ * );
+ *
+ * const exact = ixPrice.get.get.eq(ixPrice.get.toInner(9.99));
+ * ```
+ */
+export function scaled<Out, Inner extends Index<number, Out>>(
+  factor: number,
+  inner: UnregisteredIndex<number, Out, Inner>
+): UnregisteredIndex<number, Out, ScaledIndex<Out, Inner>> {
+  return ScaledIndex.create(
+    inner,
+    (v) => Math.round(v * factor),
+    (v) => v / factor
+  );
+}
+
+/**
+ * Rounds the values down to a multiple of `step` before indexing them. For
+ * example `quantized(60_000, ...)` indexes millisecond timestamps by the
+ * minute. Unlike {@link bucketed}, the inner index sees every item, with the
+ * rounded value.
+ *
+ * @example
+ * ```typescript
+ * const ixCreated = collection.registerIndex(
+ *   premap((e) => e.createdAt, quantized(60_000, hashIndex()))
+ * );
+ *
+ * const sameMinute = ixCreated.get.get.eq(ixCreated.get.toInner(Date.now()));
+ * ```
+ */
+export function quantized<Out, Inner extends Index<number, Out>>(
+  step: number,
+  inner: UnregisteredIndex<number, Out, Inner>
+): UnregisteredIndex<number, Out, ScaledIndex<Out, Inner>> {
+  if (!(step > 0)) {
+    throw new Error("composable-indexes: step must be positive");
+  }
+  return ScaledIndex.create(
+    inner,
+    (v) => Math.floor(v / step) * step,
+    (v) => v
+  );
+}
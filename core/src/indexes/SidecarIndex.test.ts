@@ -0,0 +1,41 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { Collection } from "../core/Collection";
+import { sidecarIndex } from "./SidecarIndex";
+
+test("SidecarIndex", async () => {
+  await test("lifecycle", () => {
+    const c = new Collection<string>();
+    const ix = c.registerIndex(sidecarIndex<string, string, number>());
+
+    const a = c.add("foo");
+    const b = c.add("quux");
+    assert.strictEqual(ix.get(a), undefined);
+    assert.strictEqual(ix.getOrCompute(a, (v) => v.length), 3);
+    assert.strictEqual(ix.getOrCompute(a, () => 0), 3);
+    assert.ok(ix.set(b, 4));
+
+    c.set(a, "foobar");
+    assert.strictEqual(ix.get(a), undefined);
+    assert.strictEqual(ix.get(b), 4);
+
+    c.delete(b);
+    assert.strictEqual(ix.get(b), undefined);
+    assert.ok(!ix.set(b, 4));
+    assert.strictEqual(ix.getOrCompute(b, () => 0), undefined);
+  });
+
+  await test("keepOnUpdate", () => {
+    const c = new Collection<string>();
+    const ix = c.registerIndex(
+      sidecarIndex<string, string, string>({ keepOnUpdate: true })
+    );
+
+    const a = c.add("foo");
+    ix.set(a, "meta");
+    c.set(a, "bar");
+    assert.strictEqual(ix.get(a), "meta");
+    c.delete(a);
+    assert.strictEqual(ix.get(a), undefined);
+  });
+});
@@ -0,0 +1,116 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { IdMap, unreachable } from "../util";
+
+/**
+ * Attaches metadata to items without storing it in the items themselves,
+ * for example to cache an expensive computation per item. The metadata of
+ * an item is dropped when the item is deleted, and by default also when it
+ * is updated, since it is likely derived from the old value.
+ *
+ * Memory footprint: `O(m)` where `m` is the number of items with metadata
+ *
+ * @see {@link sidecarIndex} as a constructor.
+ */
+export class SidecarIndex<In, Out, M> extends Index<In, Out> {
+  private readonly metadata: IdMap<M> = new IdMap();
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly keepOnUpdate: boolean
+  ) {
+    super(ctx);
+  }
+
+  static create<In, Out, M>(
+    keepOnUpdate: boolean
+  ): UnregisteredIndex<In, Out, SidecarIndex<In, Out, M>> {
+    return new UnregisteredIndex(
+      (ctx) => new SidecarIndex(ctx, keepOnUpdate)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        // Nothing to do until metadata is attached.
+      } else if (update.type === UpdateType.UPDATE) {
+        if (!this.keepOnUpdate) this.metadata.delete(update.id);
+      } else if (update.type === UpdateType.DELETE) {
+        this.metadata.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  /**
+   * Attaches metadata to an item, replacing any existing one.
+   *
+   * Complexity: `O(1)`
+   *
+   * @returns Whether the item exists; metadata is not attached otherwise.
+   */
+  set(id: Id, metadata: M): boolean {
+    if (this._indexContext.get(id) === undefined) return false;
+    this.metadata.set(id, metadata);
+    return true;
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  get(id: Id): M | undefined {
+    return this.metadata.get(id);
+  }
+
+  /**
+   * The metadata of an item, computing and attaching it with `f` if it has
+   * none.
+   *
+   * Complexity: `O(1)` plus the cost of `f`
+   *
+   * @returns `undefined` if the item doesn't exist.
+   */
+  getOrCompute(id: Id, f: (value: Out) => M): M | undefined {
+    const existing = this.metadata.get(id);
+    if (existing !== undefined) return existing;
+
+    const value = this._indexContext.get(id);
+    if (value === undefined) return undefined;
+    const computed = f(value);
+    this.metadata.set(id, computed);
+    return computed;
+  }
+
+  /**
+   * Complexity: `O(1)`
+   */
+  delete(id: Id): void {
+    this.metadata.delete(id);
+  }
+}
+
+/**
+ * Create a new {@link SidecarIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixThumbnails = collection.registerIndex(sidecarIndex<Image, Image, Blob>());
+ *
+ * const thumbnail = ixThumbnails.getOrCompute(id, (image) => render(image));
+ * ```
+ *
+ * @param options.keepOnUpdate Keep the metadata when the item is updated.
+ */
+export function sidecarIndex<In, Out, M>(options?: {
+  keepOnUpdate?: boolean;
+}): UnregisteredIndex<In, Out, SidecarIndex<In, Out, M>> {
+  return SidecarIndex.create(options?.keepOnUpdate ?? false);
+}
@@ -0,0 +1,87 @@
+/**
+ * Turns text into the tokens stored by text indexes.
+ *
+ * Analyzers are immutable; every builder method returns a new analyzer. The
+ * default one splits on anything that is not a letter or a digit, and
+ * lowercases the results.
+ *
+ * Tokens go through the pipeline in the following order: tokenizer,
+ * lowercasing, stop-word removal, stemming. Stop-words are compared after
+ * lowercasing.
+ *
+ * @example
+ * ```typescript
+ * const analyzer = TextAnalyzer.default()
+ *   .stopWords(ENGLISH_STOP_WORDS)
+ *   .stemmer((token) => token.replace(/s$/, ""));
+ *
+ * const ixDescription = collection.registerIndex(
+ *   premap((p) => p.description, textIndex({ analyzer }))
+ * );
+ * ```
+ */
+export class TextAnalyzer {
+  private constructor(
+    private readonly steps: {
+      readonly tokenizer: (text: string) => string[];
+      readonly lowercase: boolean;
+      readonly stopWords: ReadonlySet<string>;
+      readonly stemmer: ((token: string) => string) | undefined;
+    }
+  ) {}
+
+  static default(): TextAnalyzer {
+    return new TextAnalyzer({
+      tokenizer: (text) => text.split(/[^\p{L}\p{N}]+/u),
+      lowercase: true,
+      stopWords: new Set(),
+      stemmer: undefined,
+    });
+  }
+
+  /** Replaces the function splitting the text into tokens. */
+  tokenizer(f: (text: string) => string[]): TextAnalyzer {
+    return new TextAnalyzer({ ...this.steps, tokenizer: f });
+  }
+
+  lowercase(enabled: boolean = true): TextAnalyzer {
+    return new TextAnalyzer({ ...this.steps, lowercase: enabled });
+  }
+
+  /** Adds to the set of tokens that are dropped. */
+  stopWords(words: Iterable<string>): TextAnalyzer {
+    const stopWords = new Set(this.steps.stopWords);
+    for (const word of words) {
+      stopWords.add(this.steps.lowercase ? word.toLowerCase() : word);
+    }
+    return new TextAnalyzer({ ...this.steps, stopWords });
+  }
+
+  /** Sets a function reducing each token to its stem. */
+  stemmer(f: (token: string) => string): TextAnalyzer {
+    return new TextAnalyzer({ ...this.steps, stemmer: f });
+  }
+
+  analyze(text: string): string[] {
+    const { tokenizer, lowercase, stopWords, stemmer } = this.steps;
+
+    const ret: string[] = [];
+    for (let token of tokenizer(text)) {
+      if (lowercase) token = token.toLowerCase();
+      if (token.length === 0 || stopWords.has(token)) continue;
+      ret.push(stemmer ? stemmer(token) : token);
+    }
+    return ret;
+  }
+}
+
+/**
+ * A short list of common English words, to be used with
+ * {@link TextAnalyzer.stopWords}.
+ */
+export const ENGLISH_STOP_WORDS: readonly string[] = [
+  "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in",
+  "into", "is", "it", "no", "not", "of", "on", "or", "such", "that", "the",
+  "their", "then", "there", "these", "they", "this", "to", "was", "will",
+  "with",
+];
@@ -0,0 +1,92 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { TextIndex, textIndex } from "./TextIndex";
+import fc from "fast-check";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { testProps } from "../test_util/invariants";
+import { Collection } from "../core/Collection";
+import { ENGLISH_STOP_WORDS, TextAnalyzer } from "./TextAnalyzer";
+
+const textGen = fc
+  .array(fc.constantFrom("error", "timeout", "ok", "Retry"), { maxLength: 5 })
+  .map((ws) => ws.join(" "));
+
+test("TextIndex", async () => {
+  await test("ref.search", () => {
+    fc.assert(
+      propIndexAgainstReference<string, TextIndex<string>, string[]>({
+        valueGen: textGen,
+        index: textIndex(),
+        value: (ix) =>
+          ix
+            .search("retry")
+            .map((it) => it.id.asLong.toString())
+            .sort(),
+        reference: (arr) =>
+          arr
+            .filter((it) => it.value.split(" ").includes("Retry"))
+            .map((it) => it.id.asLong.toString())
+            .sort(),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("ranking", () => {
+    const c = new Collection<string>();
+    const ix = c.registerIndex(textIndex());
+
+    c.add("connection ok");
+    const both = c.add("Error: connection timeout");
+    const error = c.add("error error");
+    c.add("all good");
+
+    assert.deepEqual(
+      ix.search("error timeout").map((it) => it.id),
+      [both, error]
+    );
+    assert.deepEqual(
+      ix.search("error timeout", 1).map((it) => it.id),
+      [both]
+    );
+    assert.deepEqual(ix.search("missing"), []);
+  });
+
+  await test("analyzer", () => {
+    const c = new Collection<string>();
+    const analyzer = TextAnalyzer.default()
+      .stopWords(ENGLISH_STOP_WORDS)
+      .stemmer((token) => token.replace(/s$/, ""));
+    const ix = c.registerIndex(textIndex({ analyzer }));
+
+    const timeouts = c.add("The Timeouts");
+    c.add("the error");
+
+    assert.deepEqual(analyzer.analyze("The Timeouts"), ["timeout"]);
+    assert.deepEqual(
+      ix.search("timeout").map((it) => it.id),
+      [timeouts]
+    );
+    assert.deepEqual(ix.search("the"), []);
+  });
+
+  const witnesses = {
+    search: (ix: TextIndex<string>) =>
+      ix
+        .search("error timeout")
+        .map((it) => it.value)
+        .sort(),
+  };
+
+  for (const [name, witness] of Object.entries(witnesses)) {
+    await test(`props.${name}`, async (t) => {
+      await testProps<string, TextIndex<string>, any>(t, {
+        valueGen: textGen,
+        index: textIndex(),
+        witness,
+      });
+    });
+  }
+});
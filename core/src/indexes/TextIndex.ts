@@ -0,0 +1,184 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { IdMap, unreachable } from "../util";
+import { TextAnalyzer } from "./TextAnalyzer";
+
+/**
+ * A full-text index over strings, ranking matches with
+ * [BM25](https://en.wikipedia.org/wiki/Okapi_BM25).
+ *
+ * Keeps an inverted index from each token to the term frequencies of the items
+ * containing it, alongside the length of every indexed text.
+ *
+ * Texts and queries are split into tokens using a {@link TextAnalyzer}.
+ *
+ * Memory footprint: `O(t)` where `t` is the total number of tokens indexed.
+ *
+ * @see {@link textIndex} as a constructor.
+ */
+export class TextIndex<Out> extends Index<string, Out> {
+  private readonly postings: Map<string, IdMap<number>> = new Map();
+  private readonly lengths: IdMap<number> = new IdMap();
+  private docCount = 0;
+  private totalLength = 0;
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly analyzer: TextAnalyzer,
+    private readonly k1: number,
+    private readonly b: number
+  ) {
+    super(ctx);
+  }
+
+  static create<Out>(args?: {
+    analyzer?: TextAnalyzer;
+    k1?: number;
+    b?: number;
+  }): UnregisteredIndex<string, Out, TextIndex<Out>> {
+    return new UnregisteredIndex(
+      (ctx) =>
+        new TextIndex(
+          ctx,
+          args?.analyzer ?? TextAnalyzer.default(),
+          args?.k1 ?? 1.2,
+          args?.b ?? 0.75
+        )
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<string>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        if (update.oldValue === update.newValue) return;
+        this.delete(update.id, update.oldValue);
+        this.add(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id, update.oldValue);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, text: string): void {
+    const tokens = this.analyzer.analyze(text);
+
+    for (const [token, tf] of termFrequencies(tokens)) {
+      let posting = this.postings.get(token);
+      if (posting === undefined) {
+        posting = new IdMap();
+        this.postings.set(token, posting);
+      }
+      posting.set(id, tf);
+    }
+
+    this.lengths.set(id, tokens.length);
+    this.docCount += 1;
+    this.totalLength += tokens.length;
+  }
+
+  private delete(id: Id, text: string): void {
+    const tokens = this.analyzer.analyze(text);
+
+    for (const token of new Set(tokens)) {
+      const posting = this.postings.get(token);
+      posting?.delete(id);
+      if (posting && posting.empty()) {
+        this.postings.delete(token);
+      }
+    }
+
+    this.lengths.delete(id);
+    this.docCount -= 1;
+    this.totalLength -= tokens.length;
+  }
+
+  // Queries
+
+  /**
+   * Includes the number of distinct tokens and indexed documents, and the
+   * most documents sharing a token.
+   */
+  override describe(): IndexDescription {
+    let maxDocumentsPerToken = 0;
+    for (const docs of this.postings.values()) {
+      maxDocumentsPerToken = Math.max(maxDocumentsPerToken, docs.size);
+    }
+    return {
+      ...super.describe(),
+      parameters: {
+        tokens: this.postings.size,
+        documents: this.lengths.size,
+        maxDocumentsPerToken,
+      },
+    };
+  }
+
+  /**
+   * Returns the items matching any token of `query`, best matches first.
+   *
+   * Complexity: `O(m * log(m))` where `m` is the number of items containing
+   * at least one of the query tokens.
+   */
+  search(query: string, limit?: number): Item<Out>[] {
+    const scores = new IdMap<number>();
+    const avgLength = this.docCount > 0 ? this.totalLength / this.docCount : 0;
+
+    for (const token of new Set(this.analyzer.analyze(query))) {
+      const posting = this.postings.get(token);
+      if (posting === undefined) continue;
+
+      const df = posting.size;
+      const idf = Math.log(1 + (this.docCount - df + 0.5) / (df + 0.5));
+
+      posting.forEach((tf, id) => {
+        const length = this.lengths.get(id)!;
+        const norm = 1 - this.b + (this.b * length) / avgLength;
+        const score = (idf * tf * (this.k1 + 1)) / (tf + this.k1 * norm);
+        scores.set(id, (scores.get(id) ?? 0) + score);
+      });
+    }
+
+    const ranked = Array.from(scores.entries());
+    ranked.sort(([idA, a], [idB, b]) => b - a || idA.compare(idB));
+
+    return ranked
+      .slice(0, limit ?? ranked.length)
+      .map(([id]) => this.item(id));
+  }
+}
+
+function termFrequencies(tokens: string[]): Map<string, number> {
+  const ret = new Map<string, number>();
+  for (const token of tokens) {
+    ret.set(token, (ret.get(token) ?? 0) + 1);
+  }
+  return ret;
+}
+
+/**
+ * Create a new {@link TextIndex}.
+ *
+ * @param args.analyzer Used for both the indexed texts and the queries.
+ * Defaults to {@link TextAnalyzer.default}.
+ * @param args.k1 BM25 term frequency saturation. Defaults to `1.2`.
+ * @param args.b BM25 length normalization. Defaults to `0.75`.
+ */
+export function textIndex<Out>(args?: {
+  analyzer?: TextAnalyzer;
+  k1?: number;
+  b?: number;
+}): UnregisteredIndex<string, Out, TextIndex<Out>> {
+  return TextIndex.create(args);
+}
@@ -0,0 +1,68 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { Collection } from "../core/Collection";
+import { premap } from "../core/Index";
+import { btreeIndex } from "./BTreeIndex";
+import { hashIndex } from "./HashIndex";
+import { LatencyHistogram, timed, underusedIndexes } from "./TimedIndex";
+
+test("TimedIndex", async () => {
+  await test("LatencyHistogram", () => {
+    const h = new LatencyHistogram([1, 10]);
+    for (const ms of [0.5, 1, 5, 10, 50]) h.record(ms);
+
+    assert.deepEqual(h.counts, [2, 2, 1]);
+    assert.strictEqual(h.count, 5);
+    assert.strictEqual(h.max, 50);
+    assert.strictEqual(h.mean(), 66.5 / 5);
+    assert.strictEqual(new LatencyHistogram([1]).mean(), 0);
+  });
+
+  await test("latencies", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(timed(btreeIndex<number, number>()));
+
+    const ids = c.addAll([1, 2, 3, 4]);
+    c.set(ids[0], 5);
+    c.deleteMany([ids[1], ids[2]]);
+    c.delete(ids[3]);
+    const max = ix.query((inner) => inner.max1()?.value);
+
+    assert.strictEqual(max, 5);
+    assert.deepEqual(ix.get.sorted().map((i) => i.value), [5]);
+    assert.strictEqual(ix.latencies("add").count, 4);
+    assert.strictEqual(ix.latencies("update").count, 1);
+    assert.strictEqual(ix.latencies("delete").count, 3);
+    assert.strictEqual(ix.latencies("query").count, 1);
+  });
+
+  await test("queryRatio", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(timed(btreeIndex<number, number>()));
+    assert.strictEqual(ix.queryRatio(), undefined);
+
+    const id = c.add(1);
+    c.set(id, 2);
+    c.add(3);
+    c.delete(id);
+    ix.query((inner) => inner.max());
+
+    assert.strictEqual(ix.writes(), 4);
+    assert.strictEqual(ix.queryRatio(), 1 / 4);
+  });
+
+  await test("underusedIndexes", () => {
+    const c = new Collection<number>();
+    const queried = c.registerNamedIndex("queried", timed(btreeIndex<number, number>()));
+    c.registerNamedIndex("unqueried", timed(premap((v: number) => v % 2, hashIndex())));
+    c.registerNamedIndex("untimed", hashIndex());
+
+    c.addAll([1, 2, 3, 4]);
+    queried.query((inner) => inner.min());
+
+    assert.deepEqual(underusedIndexes(c, { minWrites: 4, maxQueryRatio: 0.1 }), [
+      "unqueried",
+    ]);
+    assert.deepEqual(underusedIndexes(c, { minWrites: 5, maxQueryRatio: 0.1 }), []);
+  });
+});
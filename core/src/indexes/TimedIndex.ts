@@ -0,0 +1,229 @@
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
+import type { Collection } from "../core/Collection";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+
+export type TimedOperation = "add" | "update" | "delete" | "query";
+
+/**
+ * A histogram of latencies in milliseconds, with fixed bucket boundaries.
+ */
+export class LatencyHistogram {
+  /** The number of recorded latencies. */
+  count = 0;
+  /** The sum of recorded latencies. */
+  total = 0;
+  /** The largest recorded latency. */
+  max = 0;
+  /**
+   * `counts[i]` is the number of latencies at most `bounds[i]`, and greater
+   * than `bounds[i - 1]`. The last element counts the ones above all bounds.
+   */
+  readonly counts: number[];
+
+  constructor(readonly bounds: readonly number[]) {
+    this.counts = new Array(bounds.length + 1).fill(0);
+  }
+
+  record(ms: number): void {
+    this.count++;
+    this.total += ms;
+    this.max = Math.max(this.max, ms);
+
+    let i = 0;
+    while (i < this.bounds.length && ms > this.bounds[i]) i++;
+    this.counts[i]++;
+  }
+
+  mean(): number {
+    return this.count === 0 ? 0 : this.total / this.count;
+  }
+}
+
+/**
+ * Wraps an index, recording the latency of every operation on it.
+ *
+ * Updates are timed automatically; a batch of updates, like the one from
+ * {@link Collection.deleteMany}, is recorded as its average latency per
+ * update. Queries are only timed when they go through
+ * {@link TimedIndex.query}.
+ *
+ * @see {@link timed} as a constructor.
+ */
+export class TimedIndex<In, Out, Inner extends Index<In, Out>> extends Index<
+  In,
+  Out
+> {
+  private readonly histograms: Record<TimedOperation, LatencyHistogram>;
+
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly inner: Inner,
+    bounds: readonly number[]
+  ) {
+    super(ctx);
+    this.histograms = {
+      add: new LatencyHistogram(bounds),
+      update: new LatencyHistogram(bounds),
+      delete: new LatencyHistogram(bounds),
+      query: new LatencyHistogram(bounds),
+    };
+  }
+
+  static create<In, Out, Inner extends Index<In, Out>>(
+    inner: UnregisteredIndex<In, Out, Inner>,
+    bounds: readonly number[]
+  ): UnregisteredIndex<In, Out, TimedIndex<In, Out, Inner>> {
+    return new UnregisteredIndex(
+      (ctx) => new TimedIndex(ctx, inner._register(ctx), bounds)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    const histogram = this.histograms[operation(update)];
+
+    const start = performance.now();
+    const hook = this.inner._onUpdate(update);
+    const elapsed = performance.now() - start;
+
+    return () => {
+      const start = performance.now();
+      hook();
+      histogram.record(elapsed + performance.now() - start);
+    };
+  }
+
+  /** @internal */
+  override _onUpdates(updates: Update<In>[]): () => void {
+    const start = performance.now();
+    const hook = this.inner._onUpdates(updates);
+    const elapsed = performance.now() - start;
+
+    return () => {
+      const start = performance.now();
+      hook();
+      const perUpdate = (elapsed + performance.now() - start) / updates.length;
+      for (const update of updates) {
+        this.histograms[operation(update)].record(perUpdate);
+      }
+    };
+  }
+
+  /**
+   * Runs a query on the inner index, recording its latency.
+   */
+  query<R>(f: (inner: Inner) => R): R {
+    const start = performance.now();
+    try {
+      return f(this.inner);
+    } finally {
+      this.histograms.query.record(performance.now() - start);
+    }
+  }
+
+  /**
+   * The latencies recorded for the given operation.
+   */
+  latencies(op: TimedOperation): LatencyHistogram {
+    return this.histograms[op];
+  }
+
+  /**
+   * The number of queries per update, or `undefined` before the first
+   * update. A low ratio over a long time suggests that the index costs more
+   * than it is worth.
+   */
+  queryRatio(): number | undefined {
+    const writes = this.writes();
+    return writes === 0 ? undefined : this.histograms.query.count / writes;
+  }
+
+  /**
+   * The number of updates the index received.
+   */
+  writes(): number {
+    const { add, update, delete: del } = this.histograms;
+    return add.count + update.count + del.count;
+  }
+
+  override describe(): IndexDescription {
+    return { ...super.describe(), children: [this.inner.describe()] };
+  }
+
+  /**
+   * The inner index, for queries that should not be timed.
+   */
+  get get(): Inner {
+    return this.inner;
+  }
+}
+
+function operation(update: Update<unknown>): TimedOperation {
+  switch (update.type) {
+    case UpdateType.ADD:
+      return "add";
+    case UpdateType.UPDATE:
+      return "update";
+    case UpdateType.DELETE:
+      return "delete";
+    default:
+      unreachable(update);
+  }
+}
+
+/**
+ * The names of the {@link TimedIndex}es registered with
+ * {@link Collection.registerNamedIndex} that received at least `minWrites`
+ * updates, but at most `maxQueryRatio` queries per update. These are
+ * candidates for removal.
+ *
+ * @example
+ * ```typescript
+ * collection.registerNamedIndex("byAge", timed(premap((p) => p.age, btreeIndex())));
+ * // ...
+ * for (const name of underusedIndexes(collection, { minWrites: 1e6, maxQueryRatio: 1e-5 })) {
+ *   console.warn(`Index ${name} is rarely queried`);
+ * }
+ * ```
+ */
+export function underusedIndexes(
+  collection: Collection<any>,
+  policy: { minWrites: number; maxQueryRatio: number }
+): string[] {
+  return collection.indexNames().filter((name) => {
+    const ix = collection.namedIndex(name);
+    return (
+      ix instanceof TimedIndex &&
+      ix.writes() >= policy.minWrites &&
+      ix.queryRatio()! <= policy.maxQueryRatio
+    );
+  });
+}
+
+/**
+ * Wraps an index in a {@link TimedIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixByAge = collection.registerIndex(
+ *   timed(premap((p) => p.age, btreeIndex()))
+ * );
+ *
+ * const adults = ixByAge.query((ix) => ix.get.range({ minValue: 18, maxValue: 200 }));
+ * console.log(ixByAge.latencies("query").mean());
+ * ```
+ *
+ * @param bounds Upper bounds of the histogram buckets, in milliseconds.
+ */
+export function timed<In, Out, Inner extends Index<In, Out>>(
+  inner: UnregisteredIndex<In, Out, Inner>,
+  bounds: readonly number[] = [0.01, 0.1, 1, 10, 100]
+): UnregisteredIndex<In, Out, TimedIndex<In, Out, Inner>> {
+  return TimedIndex.create(inner, bounds);
+}
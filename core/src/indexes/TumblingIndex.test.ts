@@ -0,0 +1,59 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { tumblingIndex } from "./TumblingIndex";
+import { sumIndex } from "./FoldIndex";
+import { Collection } from "../core/Collection";
+
+test("TumblingIndex", async () => {
+  await test("windows", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(
+      tumblingIndex({ window: 10, retain: 3, inner: sumIndex() })
+    );
+
+    ix.advance(3);
+    const first = c.add(1);
+    c.add(2);
+    assert.strictEqual(ix.value(), 3);
+
+    // Still in the first window
+    ix.advance(9);
+    assert.deepEqual(ix.windows(), []);
+
+    ix.advance(12);
+    assert.deepEqual(ix.windows(), [{ start: 0, value: 3 }]);
+    assert.strictEqual(ix.value(), 0);
+
+    // Items from finalized windows are ignored
+    c.delete(first);
+    c.add(5);
+    assert.strictEqual(ix.value(), 5);
+
+    ix.advance(41);
+    assert.deepEqual(ix.windows(), [
+      { start: 10, value: 5 },
+      { start: 20, value: 0 },
+      { start: 30, value: 0 },
+    ]);
+  });
+
+  await test("as an aggregate", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(
+      tumblingIndex({ window: 10, retain: 3, inner: sumIndex() }).map((v) => v * 2)
+    );
+
+    c.add(1);
+    c.add(2);
+    assert.strictEqual(ix.value(), 6);
+  });
+
+  await test("invalid window", () => {
+    for (const window of [0, -10, NaN]) {
+      assert.throws(
+        () => tumblingIndex({ window, retain: 3, inner: sumIndex() }),
+        /composable-indexes:/
+      );
+    }
+  });
+});
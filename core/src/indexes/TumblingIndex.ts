@@ -0,0 +1,149 @@
+import { IndexContext } from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { IdSet, unreachable } from "../util";
+import { AggregateIndex, UnregisteredAggregateIndex } from "./AggregateIndex";
+
+/**
+ * An aggregate index that splits time into fixed-size windows, maintaining a
+ * fresh inner aggregate for each one.
+ *
+ * Time only moves when {@link TumblingIndex.advance} is called. Crossing a
+ * window boundary finalizes the value of the current window and starts an
+ * empty one. Items only contribute to the window they were added in; updates
+ * and deletions of items from already finalized windows are ignored.
+ *
+ * Memory footprint: `O(m + retain)` where `m` is the number of items in the
+ * current window, plus the footprint of the inner index.
+ *
+ * @see {@link tumblingIndex} as a constructor.
+ */
+export class TumblingIndex<In, Value> extends AggregateIndex<In, Value> {
+  private current: AggregateIndex<In, Value>;
+  private members: IdSet = new IdSet();
+  private windowStart: number | undefined = undefined;
+  private readonly history: { start: number; value: Value }[] = [];
+
+  private constructor(
+    ctx: IndexContext<any>,
+    private readonly inner: UnregisteredAggregateIndex<In, Value>,
+    private readonly window: number,
+    private readonly retain: number
+  ) {
+    super(ctx);
+    this.current = inner._register(ctx);
+  }
+
+  static create<In, Value>(args: {
+    window: number;
+    retain: number;
+    inner: UnregisteredAggregateIndex<In, Value>;
+  }): UnregisteredAggregateIndex<In, Value, TumblingIndex<In, Value>> {
+    if (!(args.window > 0)) {
+      throw new Error("composable-indexes: window must be positive");
+    }
+    return new UnregisteredAggregateIndex(
+      (ctx) => new TumblingIndex(ctx, args.inner, args.window, args.retain)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    if (update.type === UpdateType.ADD) {
+      const hook = this.current._onUpdate(update);
+      return () => {
+        hook();
+        this.members.set(update.id);
+      };
+    } else if (update.type === UpdateType.UPDATE) {
+      if (!this.members.has(update.id)) return () => {};
+      return this.current._onUpdate(update);
+    } else if (update.type === UpdateType.DELETE) {
+      if (!this.members.has(update.id)) return () => {};
+      const hook = this.current._onUpdate(update);
+      return () => {
+        hook();
+        this.members.delete(update.id);
+      };
+    } else {
+      unreachable(update);
+    }
+  }
+
+  /**
+   * Moves the clock to `now`, finalizing the current window if `now` is past
+   * its end. The first call starts the first window.
+   *
+   * Windows are aligned to multiples of the window size, and windows without
+   * any items in between are finalized with the value of an empty inner index.
+   */
+  advance(now: number): void {
+    const start = Math.floor(now / this.window) * this.window;
+
+    if (this.windowStart === undefined) {
+      this.windowStart = start;
+      return;
+    }
+    if (start <= this.windowStart) return;
+
+    this.history.push({ start: this.windowStart, value: this.current.value() });
+    this.current = this.inner._register(this._indexContext);
+    this.members = new IdSet();
+
+    const skipped = Math.round((start - this.windowStart) / this.window) - 1;
+    for (let i = Math.min(skipped, this.retain); i > 0; i--) {
+      this.history.push({
+        start: start - i * this.window,
+        value: this.current.value(),
+      });
+    }
+
+    this.windowStart = start;
+    if (this.history.length > this.retain) {
+      this.history.splice(0, this.history.length - this.retain);
+    }
+  }
+
+  /**
+   * The value of the current, unfinished window.
+   *
+   * Complexity: Same as the inner index.
+   */
+  override value(): Value {
+    return this.current.value();
+  }
+
+  /**
+   * The values of the last `retain` finalized windows, oldest first.
+   *
+   * Complexity: `O(1)`
+   */
+  windows(): readonly { start: number; value: Value }[] {
+    return this.history;
+  }
+}
+
+/**
+ * Create a new {@link TumblingIndex}.
+ *
+ * @example
+ * ```typescript
+ * // Requests per minute, per endpoint
+ * const ixRequestsPerMinute = collection.registerIndex(
+ *   group(
+ *     (r) => r.endpoint,
+ *     tumblingIndex({ window: 60_000, retain: 60, inner: countIndex() })
+ *   )
+ * );
+ * ```
+ *
+ * @param args.window The size of a window, in the same unit as the values
+ * passed to {@link TumblingIndex.advance}.
+ * @param args.retain How many finalized windows to keep.
+ */
+export function tumblingIndex<In, Value>(args: {
+  window: number;
+  retain: number;
+  inner: UnregisteredAggregateIndex<In, Value>;
+}): UnregisteredAggregateIndex<In, Value, TumblingIndex<In, Value>> {
+  return TumblingIndex.create(args);
+}
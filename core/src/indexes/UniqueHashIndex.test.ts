@@ -0,0 +1,62 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import { uniqueHashIndex } from "./UniqueHashIndex";
+import { premap } from "../core/Index";
+import { Collection, ConflictException } from "../core/Collection";
+
+type User = { email: string; name: string };
+
+test("UniqueHashIndex", async () => {
+  await test("asMap", () => {
+    const c = new Collection<User>();
+    const ix = c.registerIndex(premap((u) => u.email, uniqueHashIndex()));
+    const byEmail = ix.get.asMap();
+
+    const alice = { email: "alice@example.com", name: "Alice" };
+    const bob = { email: "bob@example.com", name: "Bob" };
+    c.add(alice);
+    const bobId = c.add(bob);
+
+    assert.strictEqual(byEmail.size, 2);
+    assert.strictEqual(byEmail.get("alice@example.com"), alice);
+    assert.ok(byEmail.has("bob@example.com"));
+    assert.deepEqual(Array.from(byEmail.keys()).sort(), [
+      "alice@example.com",
+      "bob@example.com",
+    ]);
+
+    c.delete(bobId);
+    assert.ok(!byEmail.has("bob@example.com"));
+    assert.deepEqual(Array.from(byEmail), [["alice@example.com", alice]]);
+  });
+
+  await test("normalized", () => {
+    const c = new Collection<User>();
+    const ix = c.registerIndex(
+      premap((u) => u.name.toLowerCase(), uniqueHashIndex())
+    );
+
+    const alice = c.add({ email: "alice@example.com", name: "Alice" });
+    const bob = c.add({ email: "bob@example.com", name: "Bob" });
+
+    assert.throws(
+      () => c.add({ email: "other@example.com", name: "ALICE" }),
+      (e) =>
+        e instanceof ConflictException &&
+        e.existingId.equals(alice) &&
+        e.existingValue.name === "Alice"
+    );
+    assert.throws(
+      () => c.adjust(alice, (u) => ({ ...u, name: "bob" })),
+      (e) =>
+        e instanceof ConflictException &&
+        e.existingId.equals(bob) &&
+        e.existingValue.name === "Bob"
+    );
+    assert.strictEqual(c.get(alice)?.name, "Alice");
+
+    c.adjust(alice, (u) => ({ ...u, name: "ALICE" }));
+    assert.strictEqual(ix.get.eq("alice")?.value.name, "ALICE");
+    assert.strictEqual(ix.get.countDistinct(), 2);
+  });
+});
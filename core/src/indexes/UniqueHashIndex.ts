@@ -2,6 +2,7 @@ import { ConflictException, Id } from "..";
 import {
   Index,
   IndexContext,
+  IndexDescription,
   UnregisteredIndex,
 } from "../core/Index";
 import { Update, UpdateType } from "../core/Update";
@@ -12,6 +13,18 @@ import { unreachable } from "../util";
  * Indexes items using a JavaScript `Map`, and ensures uniqueness.
  * 
  * Throws a {@link ConflictException} if a duplicate item is added.
+ *
+ * Combine with {@link premap} to enforce uniqueness on a normalized form,
+ * while the items keep the original:
+ *
+ * ```typescript
+ * const ixUsername = collection.registerIndex(
+ *   premap((u) => u.username.toLowerCase(), uniqueHashIndex())
+ * );
+ *
+ * // Returns the user with their own casing, e.g. "Alice".
+ * ixUsername.get.eq("ALICE".toLowerCase());
+ * ```
  */
 export class UniqueHashIndex<In extends number | string, Out> extends Index<
   In,
@@ -32,13 +45,18 @@ export class UniqueHashIndex<In extends number | string, Out> extends Index<
   }
 
   _onUpdate(update: Update<In>): () => void {
-    if (update.type === UpdateType.ADD && this.ix.has(update.value)) {
-      throw new ConflictException(update.id, this);
-    } else if (
-      update.type === UpdateType.UPDATE &&
-      this.ix.has(update.newValue)
-    ) {
-      throw new ConflictException(update.id, this);
+    if (update.type === UpdateType.ADD) {
+      const existing = this.ix.get(update.value);
+      if (existing !== undefined) {
+        throw new ConflictException(existing, this);
+      }
+    } else if (update.type === UpdateType.UPDATE) {
+      // An item keeping its value, like a user changing the casing of a
+      // username indexed case-insensitively, does not conflict with itself.
+      const existing = this.ix.get(update.newValue);
+      if (existing !== undefined && !existing.equals(update.id)) {
+        throw new ConflictException(existing, this);
+      }
     }
 
     return () => {
@@ -59,6 +77,7 @@ export class UniqueHashIndex<In extends number | string, Out> extends Index<
   }
 
   private update(id: Id, oldValue: In, newValue: In): void {
+    if (oldValue === newValue) return;
     this.ix.delete(oldValue);
     this.ix.set(newValue, id);
   }
@@ -68,6 +87,11 @@ export class UniqueHashIndex<In extends number | string, Out> extends Index<
   }
 
   // Queries
+
+  override describe(): IndexDescription {
+    return { ...super.describe(), parameters: { distinctValues: this.ix.size } };
+  }
+
   countDistinct(): number {
     return this.ix.size;
   }
@@ -76,6 +100,66 @@ export class UniqueHashIndex<In extends number | string, Out> extends Index<
     const id = this.ix.get(value);
     return id ? this.item(id) : undefined;
   }
+
+  has(value: In): boolean {
+    return this.ix.has(value);
+  }
+
+  /**
+   * A read-only `Map` from the indexed values to the items, for code
+   * expecting a `Map`. It reflects the later changes to the collection.
+   *
+   * Complexity: `O(1)`
+   */
+  asMap(): ReadonlyMap<In, Out> {
+    return new UniqueHashIndexMap(this.ix, (id) => this._indexContext.get(id)!);
+  }
+}
+
+class UniqueHashIndexMap<In, Out> implements ReadonlyMap<In, Out> {
+  constructor(
+    private readonly ix: Map<In, Id>,
+    private readonly lookup: (id: Id) => Out
+  ) {}
+
+  get size(): number {
+    return this.ix.size;
+  }
+
+  get(key: In): Out | undefined {
+    const id = this.ix.get(key);
+    return id === undefined ? undefined : this.lookup(id);
+  }
+
+  has(key: In): boolean {
+    return this.ix.has(key);
+  }
+
+  forEach(cb: (value: Out, key: In, map: ReadonlyMap<In, Out>) => void): void {
+    for (const [key, value] of this.entries()) {
+      cb(value, key, this);
+    }
+  }
+
+  *entries(): IterableIterator<[In, Out]> {
+    for (const [key, id] of this.ix) {
+      yield [key, this.lookup(id)];
+    }
+  }
+
+  keys(): IterableIterator<In> {
+    return this.ix.keys();
+  }
+
+  *values(): IterableIterator<Out> {
+    for (const id of this.ix.values()) {
+      yield this.lookup(id);
+    }
+  }
+
+  [Symbol.iterator](): IterableIterator<[In, Out]> {
+    return this.entries();
+  }
 }
 
 export function uniqueHashIndex<T extends string | number, Out>(): UnregisteredIndex<
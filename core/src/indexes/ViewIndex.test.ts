@@ -0,0 +1,87 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { ViewIndex, viewIndex } from "./ViewIndex";
+import { Collection } from "../core/Collection";
+import { propIndexAgainstReference } from "../test_util/reference";
+
+type Rows = Map<string, number>;
+
+test("ViewIndex", async () => {
+  await test("ref", () => {
+    fc.assert(
+      propIndexAgainstReference<number, ViewIndex<number, Rows, number>, [string, number][]>({
+        valueGen: fc.integer({ min: 0, max: 20 }),
+        index: viewIndex({
+          init: () => new Map(),
+          add: (rows, value, id) => rows.set(id.toString(), value * 2),
+          delete: (rows, _, id) => rows.delete(id.toString()),
+        }),
+        value: (ix) => Array.from(ix.view).sort(),
+        reference: (arr) =>
+          arr.map((i): [string, number] => [i.id.toString(), i.value * 2]).sort(),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("ref.update", () => {
+    fc.assert(
+      propIndexAgainstReference<number, ViewIndex<number, Rows, number>, [string, number][]>({
+        valueGen: fc.integer({ min: 0, max: 20 }),
+        index: viewIndex({
+          init: () => new Map(),
+          add: (rows, value, id) => rows.set(id.toString(), value * 2),
+          delete: (rows, _, id) => rows.delete(id.toString()),
+          update: (rows, _, newValue, id) => rows.set(id.toString(), newValue * 2),
+        }),
+        value: (ix) => Array.from(ix.view).sort(),
+        reference: (arr) =>
+          arr.map((i): [string, number] => [i.id.toString(), i.value * 2]).sort(),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("handlers", () => {
+    const calls: string[] = [];
+    const handlers = {
+      init: () => null,
+      add: (_: null, value: number) => {
+        calls.push(`add ${value}`);
+      },
+      delete: (_: null, oldValue: number) => {
+        calls.push(`delete ${oldValue}`);
+      },
+    };
+
+    const c = new Collection<number>();
+    c.registerIndex(viewIndex<number, null, number>(handlers));
+    c.registerIndex(
+      viewIndex<number, null, number>({
+        ...handlers,
+        update: (_, oldValue, newValue) => {
+          calls.push(`update ${oldValue} ${newValue}`);
+        },
+      })
+    );
+
+    const id = c.add(1);
+    c.set(id, 2);
+    c.delete(id);
+
+    assert.deepEqual(calls, [
+      "add 1",
+      "add 1",
+      "delete 1",
+      "add 2",
+      "update 1 2",
+      "delete 2",
+      "delete 2",
+    ]);
+  });
+});
@@ -0,0 +1,117 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { unreachable } from "../util";
+
+/**
+ * The handlers keeping the state of a {@link ViewIndex} up to date. They are
+ * expected to mutate the state in place.
+ */
+export type ViewHandlers<In, State> = {
+  add: (state: State, value: In, id: Id) => void;
+  delete: (state: State, oldValue: In, id: Id) => void;
+  /** Defaults to a `delete` followed by an `add`. */
+  update?: (state: State, oldValue: In, newValue: In, id: Id) => void;
+};
+
+/**
+ * An index maintaining an arbitrary user-defined state, which is used as its
+ * query surface.
+ *
+ * Unlike {@link FoldIndex}, the handlers receive the {@link Id}s of the items
+ * and mutate the state in place, so the state can be an arbitrary data
+ * structure like a `Map` of derived rows, or an instance of a class with its
+ * own query methods.
+ *
+ * The time and memory complexity of this index completely depends on the
+ * handlers passed to it.
+ *
+ * @see {@link viewIndex} as a constructor.
+ */
+export class ViewIndex<In, State, Out> extends Index<In, Out> {
+  private constructor(
+    ctx: IndexContext<Out>,
+    private readonly state: State,
+    private readonly handlers: ViewHandlers<In, State>
+  ) {
+    super(ctx);
+  }
+
+  static create<In, State, Out>(
+    args: { init: () => State } & ViewHandlers<In, State>
+  ): UnregisteredIndex<In, Out, ViewIndex<In, State, Out>> {
+    return new UnregisteredIndex(
+      (ctx) => new ViewIndex(ctx, args.init(), args)
+    );
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<In>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.handlers.add(this.state, update.value, update.id);
+      } else if (update.type === UpdateType.UPDATE) {
+        if (this.handlers.update) {
+          this.handlers.update(
+            this.state,
+            update.oldValue,
+            update.newValue,
+            update.id
+          );
+        } else {
+          this.handlers.delete(this.state, update.oldValue, update.id);
+          this.handlers.add(this.state, update.newValue, update.id);
+        }
+      } else if (update.type === UpdateType.DELETE) {
+        this.handlers.delete(this.state, update.oldValue, update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  /**
+   * The maintained state. It should not be mutated outside of the handlers.
+   */
+  get view(): State {
+    return this.state;
+  }
+}
+
+/**
+ * Create a new {@link ViewIndex}.
+ *
+ * @example
+ * ```typescript
+ * // Maintain the set of distinct tags per author
+ * const ixTagsByAuthor = collection.registerIndex(
+ *   viewIndex({
+ *     init: () => new Map<string, Map<string, number>>(),
+ *     add: (view, post) => {
+ *       const tags = view.get(post.author) ?? new Map();
+ *       for (const tag of post.tags) tags.set(tag, (tags.get(tag) ?? 0) + 1);
+ *       view.set(post.author, tags);
+ *     },
+ *     delete: (view, post) => {
+ *       const tags = view.get(post.author)!;
+ *       for (const tag of post.tags) {
+ *         const n = tags.get(tag)! - 1;
+ *         n === 0 ? tags.delete(tag) : tags.set(tag, n);
+ *       }
+ *       if (tags.size === 0) view.delete(post.author);
+ *     },
+ *   })
+ * );
+ *
+ * console.log(ixTagsByAuthor.view.get("alice")?.keys());
+ * ```
+ */
+export function viewIndex<In, State, Out>(
+  args: { init: () => State } & ViewHandlers<In, State>
+): UnregisteredIndex<In, Out, ViewIndex<In, State, Out>> {
+  return ViewIndex.create(args);
+}
@@ -0,0 +1,138 @@
+import test from "node:test";
+import { strict as assert } from "node:assert";
+import fc from "fast-check";
+import { WeightedIndex, weightedIndex } from "./WeightedIndex";
+import { Collection } from "../core/Collection";
+import { propIndexAgainstReference } from "../test_util/reference";
+import { arbCalls, playCalls } from "../test_util/call";
+
+test("WeightedIndex", async () => {
+  await test("WeightedIndex.ref.totalWeight", () => {
+    fc.assert(
+      propIndexAgainstReference<number, WeightedIndex<number>, number>({
+        valueGen: fc.integer({ min: -5, max: 100 }),
+        index: weightedIndex(),
+        value: (ix) => ix.totalWeight(),
+        reference: (arr) =>
+          arr.map((i) => Math.max(i.value, 0)).reduce((a, b) => a + b, 0),
+      }),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("WeightedIndex.ref.sample", () => {
+    fc.assert(
+      fc.property(
+        fc.array(fc.integer({ min: 0, max: 3 }), { maxLength: 40 }),
+        fc.nat({ max: 999 }).map((n) => n / 1000),
+        (weights, u) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(weightedIndex());
+          const ids = c.addAll(weights);
+          ids.filter((_, i) => i % 3 === 0).forEach((id) => c.delete(id));
+
+          const picked = ix.sample(() => u);
+          if (ix.totalWeight() === 0) {
+            assert.strictEqual(picked, undefined);
+          } else {
+            assert.ok(picked !== undefined && picked.value > 0);
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("WeightedIndex.ref.fractional", () => {
+    fc.assert(
+      fc.property(
+        arbCalls({
+          value: fc.double({ min: 0, max: 1, noNaN: true }),
+          idRange: 10,
+          maxLength: 100,
+        }),
+        fc.nat({ max: 999 }).map((n) => n / 1000),
+        (calls, u) => {
+          const c = new Collection<number>();
+          const ix = c.registerIndex(weightedIndex());
+          playCalls(c, calls);
+
+          const expected = c
+            .toList()
+            .map(([, value]) => value)
+            .reduce((a, b) => a + b, 0);
+          assert.ok(Math.abs(ix.totalWeight() - expected) <= 1e-9);
+
+          const picked = ix.sample(() => u);
+          if (expected === 0) {
+            assert.strictEqual(ix.totalWeight(), 0);
+            assert.strictEqual(picked, undefined);
+          } else {
+            assert.ok(picked !== undefined && picked.value > 0);
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("WeightedIndex.empty after fractional weights", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(weightedIndex());
+    c.deleteMany(c.addAll([0.1, 0.2, 0.7]));
+
+    assert.strictEqual(ix.totalWeight(), 0);
+    assert.strictEqual(ix.sample(() => 0.5), undefined);
+  });
+
+  await test("WeightedIndex.deterministic", () => {
+    // A small linear congruential generator, seeded the same for both runs.
+    const seeded = (seed: number) => () => {
+      seed = (seed * 1664525 + 1013904223) % 2 ** 32;
+      return seed / 2 ** 32;
+    };
+
+    const run = () => {
+      const c = new Collection<number>();
+      const ix = c.registerIndex(weightedIndex());
+      const ids = c.addAll([1, 2, 3, 4, 5]);
+      c.delete(ids[1]);
+      c.add(6);
+      c.set(ids[3], 0.5);
+
+      const random = seeded(42);
+      return Array.from({ length: 50 }, () => ix.sample(random)!.value);
+    };
+
+    assert.deepEqual(run(), run());
+  });
+
+  await test("WeightedIndex.distribution", () => {
+    const c = new Collection<number>();
+    const ix = c.registerIndex(weightedIndex());
+    c.addAll([1, 0, 3]);
+
+    // A fixed sequence covering [0, 1) evenly.
+    let i = 0;
+    const random = () => (i++ % 400) / 400;
+    const counts = new Map<number, number>();
+    for (let n = 0; n < 400; n++) {
+      const weight = ix.sample(random)!.value;
+      counts.set(weight, (counts.get(weight) ?? 0) + 1);
+    }
+
+    assert.deepEqual(
+      [...counts].sort(),
+      [
+        [1, 100],
+        [3, 300],
+      ]
+    );
+  });
+});
@@ -0,0 +1,142 @@
+import { Id } from "..";
+import {
+  Index,
+  IndexContext,
+  UnregisteredIndex,
+} from "../core/Index";
+import { Update, UpdateType } from "../core/Update";
+import { Item } from "../core/simple_types";
+import { Fenwick, IdMap, unreachable } from "../util";
+
+/**
+ * An index for picking a random item, with probability proportional to its
+ * weight. Weights that are not positive are treated as zero, so those items
+ * are never picked.
+ *
+ * Keeps the weights in a Fenwick tree, with a slot per item. Slots of
+ * deleted items are reused.
+ *
+ * Memory footprint: `O(n)`
+ *
+ * @see {@link weightedIndex} as a constructor.
+ */
+export class WeightedIndex<Out> extends Index<number, Out> {
+  private readonly weights = new Fenwick();
+  private readonly slots: IdMap<number> = new IdMap();
+  private readonly ids: (Id | undefined)[] = [];
+  private readonly free: number[] = [];
+
+  private constructor(ctx: IndexContext<Out>) {
+    super(ctx);
+  }
+
+  static create<Out>(): UnregisteredIndex<number, Out, WeightedIndex<Out>> {
+    return new UnregisteredIndex((ctx) => new WeightedIndex(ctx));
+  }
+
+  /** @internal */
+  _onUpdate(update: Update<number>): () => void {
+    return () => {
+      if (update.type === UpdateType.ADD) {
+        this.add(update.id, update.value);
+      } else if (update.type === UpdateType.UPDATE) {
+        this.update(update.id, update.newValue);
+      } else if (update.type === UpdateType.DELETE) {
+        this.delete(update.id);
+      } else {
+        unreachable(update);
+      }
+    };
+  }
+
+  private add(id: Id, weight: number): void {
+    const slot = this.free.pop() ?? this.ids.length;
+    this.ids[slot] = id;
+    this.slots.set(id, slot);
+    this.weights.set(slot, clamp(weight));
+  }
+
+  private update(id: Id, weight: number): void {
+    const slot = this.slots.get(id);
+    if (slot === undefined) {
+      this.inconsistent(`Updated item ${id} is not indexed`);
+      this.add(id, weight);
+      return;
+    }
+    this.weights.set(slot, clamp(weight));
+  }
+
+  private delete(id: Id): void {
+    const slot = this.slots.get(id);
+    if (slot === undefined) {
+      this.inconsistent(`Deleted item ${id} is not indexed`);
+      return;
+    }
+    this.weights.set(slot, 0);
+    this.ids[slot] = undefined;
+    this.slots.delete(id);
+    this.free.push(slot);
+  }
+
+  // Queries
+
+  /**
+   * Picks an item with probability proportional to its weight, or returns
+   * `undefined` if no item has a positive weight.
+   *
+   * Complexity: `O(log(n))`
+   *
+   * The pick depends only on the numbers `random` returns and on the order
+   * of the updates the index received, so replaying the same mutations with
+   * a generator seeded the same way picks the same items, in property tests
+   * or replayed simulations alike.
+   *
+   * @param random Returns a number in `[0, 1)`. Defaults to `Math.random`;
+   * pass a seeded generator for reproducible picks.
+   */
+  sample(random: () => number = Math.random): Item<Out> | undefined {
+    const total = this.weights.total();
+    if (total <= 0) return undefined;
+
+    let slot = Math.min(this.weights.search(random() * total), this.ids.length - 1);
+    // Rounding errors in the sums can land on a slot without weight; the
+    // closest one before it is the right pick.
+    while (slot > 0 && this.weights.get(slot) <= 0) slot--;
+
+    const id = this.ids[slot];
+    return id === undefined ? undefined : this.item(id);
+  }
+
+  /**
+   * The sum of the weights.
+   *
+   * Complexity: `O(log(n))`
+   */
+  totalWeight(): number {
+    return this.weights.total();
+  }
+}
+
+function clamp(weight: number): number {
+  return weight > 0 ? weight : 0;
+}
+
+/**
+ * Create a new {@link WeightedIndex}.
+ *
+ * @example
+ * ```typescript
+ * const ixByCapacity = collection.registerIndex(
+ *   premap((backend) => backend.capacity, weightedIndex())
+ * );
+ *
+ * const backend = ixByCapacity.get.sample();
+ * ```
+ */
+export function weightedIndex<Out>(): UnregisteredIndex<
+  number,
+  Out,
+  WeightedIndex<Out>
+> {
+  return WeightedIndex.create();
+}
@@ -1,4 +1,9 @@
-import { Index, IndexContext, UnregisteredIndex } from "../core/Index";
+import {
+  Index,
+  IndexContext,
+  IndexDescription,
+  UnregisteredIndex,
+} from "../core/Index";
 import { Update } from "../core/Update";
 
 export class ZipIndex<In, Out, Indexes extends Index<In, Out>[]> extends Index<In, Out> {
@@ -18,6 +23,13 @@ export class ZipIndex<In, Out, Indexes extends Index<In, Out>[]> extends Index<I
         };
     }
 
+    override describe(): IndexDescription {
+        return {
+            ...super.describe(),
+            children: this.indexes.map((ix) => ix.describe()),
+        };
+    }
+
     get(): Indexes {
         return this.indexes;
     }
@@ -38,6 +50,26 @@ type MapUnregistered<In, Out, Indexes extends Index<In, Out>[]> = {
     [I in keyof Indexes]: UnregisteredIndex<In, Out, Indexes[I]>
 }
 
+/**
+ * Combines indexes over the same input into one, updating all of them with
+ * every change.
+ * 
+ * Wrapping the zip in a {@link premap} computes a derived value once per
+ * update and shares it between the zipped indexes, instead of computing it
+ * in a separate `premap` for each.
+ * 
+ * @example
+ * ```typescript
+ * const ixNames = collection.registerIndex(
+ *   premap(
+ *     (p) => p.name.toLowerCase(),
+ *     zip(hashIndex(), btreeIndex())
+ *   )
+ * );
+ * 
+ * const [byName, sortedByName] = ixNames.get.get();
+ * ```
+ */
 export function zip<In, Out, Ix1 extends Index<In, Out>, Ix2 extends Index<In, Out>>(
     ix1: UnregisteredIndex<In, Out, Ix1>,
     ix2: UnregisteredIndex<In, Out, Ix2>
@@ -3,3 +3,31 @@ export * from './HashIndex'
 export * from './BTreeIndex'
 export * from './UniqueHashIndex'
 export * from './FoldIndex'
+export * from './TextIndex'
+export * from './TextAnalyzer'
+export * from './TumblingIndex'
+export * from './AdjacencyIndex'
+export * from './ViewIndex'
+export * from './BloomIndex'
+export * from './TimedIndex'
+export * from './GroupedAggregateIndex'
+export * from './MedianIndex'
+export * from './OptionalIndex'
+export * from './PartitionedIndex'
+export * from './WeightedIndex'
+export * from './LeaderboardIndex'
+export * from './LastNIndex'
+export * from './FlagIndex'
+export * from './MinMaxIndex'
+export * from './ExternalIdIndex'
+export * from './ProjectionIndex'
+export * from './DictionaryIndex'
+export * from './GroupedManyIndex'
+export * from './SidecarIndex'
+export * from './MinHashIndex'
+export * from './ConsistentHashIndex'
+export * from './ScaledIndex'
+export * from './GroupCountsIndex'
+export * from './CachedIndex'
+export * from './RetentionIndex'
+export * from './ZipIndex'
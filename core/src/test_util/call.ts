@@ -33,6 +33,38 @@ export function ppCalls(calls: Call<any>[]): string {
     return "[" + calls.map(ppCall).join(", ") + `]`;
 }
 
+/**
+ * Renders the calls as a test that replays them against a fresh collection,
+ * to paste into a test file when a property fails.
+ */
+export function callsToTest(calls: Call<any>[], name: string = "regression"): string {
+    const id = (id: Id) => `Id.fromLong(Long.fromString("${id.asLong.toString(10)}", true))`
+    const lines = calls.map((call) => {
+        switch (call.type) {
+            case "add":
+                return `    c.add(${literal(call.value)});`
+            case "set":
+                return `    c.set(${id(call.id)}, ${literal(call.value)});`
+            case "delete":
+                return `    c.delete(${id(call.id)});`
+        }
+    })
+    return [
+        `  await test(${JSON.stringify(name)}, () => {`,
+        `    const c = new Collection<any>();`,
+        `    // const ix = c.registerIndex(...);`,
+        ...lines,
+        `    // assert.deepEqual(...);`,
+        `  });`,
+    ].join("\n")
+}
+
+function literal(value: unknown): string {
+    // `JSON.stringify` turns these into `null`.
+    if (typeof value === "number") return Object.is(value, -0) ? "-0" : String(value)
+    return JSON.stringify(value)
+}
+
 export function arbCallAdd<T>(value: fc.Arbitrary<T>): fc.Arbitrary<Call<T>> {
     return fc.record({
         type: fc.constant<"add">("add"),
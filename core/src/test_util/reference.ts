@@ -1,7 +1,7 @@
 import fc from "fast-check";
 import { Collection } from "../core/Collection";
 import { Index, UnregisteredIndex } from "../core/Index";
-import { Call, arbCalls, playCalls } from "./call";
+import { Call, arbCalls, callsToTest, playCalls } from "./call";
 import { deepStrictEqual } from "assert";
 import { MockIndex } from "./MockIndex";
 import { Item } from "../core/simple_types";
@@ -33,6 +33,7 @@ export function propIndexAgainstReference<
       playCalls(col, calls);
 
       ctx.log(`Updates: ${JSON.stringify(mockIx.collectedUpdates)}`)
+      ctx.log(`As a test:\n${callsToTest(calls)}`)
 
     const outList = mockIx.toOutList();
       ctx.log(`OutList: ${JSON.stringify(outList)}`)
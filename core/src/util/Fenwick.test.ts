@@ -0,0 +1,63 @@
+import test from "node:test";
+import { deepStrictEqual, ok } from "node:assert";
+
+import fc from "fast-check";
+import { Fenwick } from "./Fenwick";
+
+test("Fenwick", async () => {
+  await test("ref", () => {
+    return fc.assert(
+      fc.property(
+        fc.array(
+          fc.tuple(
+            fc.integer({ min: 0, max: 40 }),
+            fc.oneof(fc.constant(0), fc.double({ min: 0, max: 10, noNaN: true }))
+          )
+        ),
+        (calls) => {
+          const tree = new Fenwick(4);
+          const ref: number[] = [];
+
+          for (const [i, value] of calls) {
+            tree.set(i, value);
+            ref[i] = value;
+          }
+
+          const values = Array.from({ length: tree.capacity }, (_, i) => ref[i] ?? 0);
+          deepStrictEqual(
+            values.map((_, i) => tree.get(i)),
+            values
+          );
+          for (let i = 0; i <= values.length; i++) {
+            const expected = values.slice(0, i).reduce((a, b) => a + b, 0);
+            ok(Math.abs(tree.prefix(i) - expected) <= 1e-9 * (1 + expected));
+          }
+          if (values.every((v) => v === 0)) {
+            deepStrictEqual(tree.total(), 0);
+          }
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+
+  await test("search", () => {
+    const tree = new Fenwick();
+    [1, 0, 2, 3].forEach((v, i) => tree.set(i, v));
+
+    deepStrictEqual(
+      [0, 0.5, 1, 2.5, 3, 5.9, 6].map((t) => tree.search(t)),
+      [0, 0, 2, 2, 3, 3, tree.capacity]
+    );
+  });
+
+  await test("empty after fractional values", () => {
+    const tree = new Fenwick();
+    [0.1, 0.2, 0.7].forEach((v, i) => tree.set(i, v));
+    [0, 1, 2].forEach((i) => tree.set(i, 0));
+
+    deepStrictEqual(tree.total(), 0);
+  });
+});
@@ -0,0 +1,105 @@
+/**
+ * A Fenwick tree (binary indexed tree) over an array of non-negative numbers,
+ * supporting point updates, prefix sums and searching by prefix sum in
+ * `O(log(n))`. Grows as needed.
+ *
+ * Sums of fractional values pick up rounding errors as values come and go,
+ * so the tree is rebuilt from the values after every `capacity` writes,
+ * keeping the drift bounded at an amortized `O(1)` per write. When every
+ * value is zero, {@link Fenwick.total} is exactly zero.
+ */
+export class Fenwick {
+    private values: Float64Array
+    private tree: Float64Array
+    private nonZero = 0
+    private writes = 0
+
+    constructor(capacity: number = 16) {
+        this.values = new Float64Array(capacity)
+        this.tree = new Float64Array(capacity + 1)
+    }
+
+    get capacity(): number {
+        return this.values.length
+    }
+
+    get(i: number): number {
+        return this.values[i] ?? 0
+    }
+
+    set(i: number, value: number) {
+        while (i >= this.capacity) {
+            this.grow()
+        }
+
+        const old = this.values[i]
+        if (old === 0 && value !== 0) this.nonZero++
+        else if (old !== 0 && value === 0) this.nonZero--
+
+        this.values[i] = value
+        if (++this.writes >= this.capacity) {
+            this.tree = build(this.values)
+            this.writes = 0
+            return
+        }
+
+        const delta = value - old
+        for (let j = i + 1; j < this.tree.length; j += j & -j) {
+            this.tree[j] += delta
+        }
+    }
+
+    /**
+     * The sum of the values in `[0, i)`.
+     */
+    prefix(i: number): number {
+        let ret = 0
+        for (let j = Math.min(i, this.capacity); j > 0; j -= j & -j) {
+            ret += this.tree[j]
+        }
+        return ret
+    }
+
+    total(): number {
+        return this.nonZero === 0 ? 0 : this.prefix(this.capacity)
+    }
+
+    /**
+     * The smallest `i` such that `prefix(i + 1) > target`, or `capacity` if
+     * there is none. Values of zero are never returned.
+     */
+    search(target: number): number {
+        let pos = 0
+        let step = 1
+        while (step * 2 <= this.capacity) step *= 2
+
+        for (; step > 0; step >>= 1) {
+            if (pos + step <= this.capacity && this.tree[pos + step] <= target) {
+                pos += step
+                target -= this.tree[pos]
+            }
+        }
+        return pos
+    }
+
+    // Doubles the capacity, rebuilding the tree.
+    private grow() {
+        const values = new Float64Array(Math.max(1, this.capacity * 2))
+        values.set(this.values)
+        this.values = values
+        this.tree = build(values)
+    }
+}
+
+// Builds the tree over `values` in linear time.
+function build(values: Float64Array): Float64Array {
+    const tree = new Float64Array(values.length + 1)
+    for (let i = 1; i < tree.length; i++) {
+        tree[i] += values[i - 1]
+        const parent = i + (i & -i)
+        if (parent < tree.length) {
+            tree[parent] += tree[i]
+        }
+    }
+    return tree
+}
@@ -0,0 +1,119 @@
+/**
+ * A binary heap, with the element that is "less than" all others at the top.
+ */
+export class Heap<T> {
+    private readonly items: T[] = []
+
+    constructor(private readonly lt: (a: T, b: T) => boolean) {}
+
+    get size(): number {
+        return this.items.length
+    }
+
+    peek(): T | undefined {
+        return this.items[0]
+    }
+
+    push(value: T) {
+        const items = this.items
+        items.push(value)
+
+        let i = items.length - 1
+        while (i > 0) {
+            const parent = (i - 1) >> 1
+            if (!this.lt(items[i], items[parent])) break
+            ;[items[i], items[parent]] = [items[parent], items[i]]
+            i = parent
+        }
+    }
+
+    pop(): T | undefined {
+        const items = this.items
+        if (items.length === 0) {
+            return undefined
+        }
+
+        const top = items[0]
+        const last = items.pop()!
+        if (items.length > 0) {
+            items[0] = last
+
+            let i = 0
+            for (;;) {
+                const left = 2 * i + 1
+                const right = left + 1
+                let smallest = i
+                if (left < items.length && this.lt(items[left], items[smallest])) smallest = left
+                if (right < items.length && this.lt(items[right], items[smallest])) smallest = right
+                if (smallest === i) break
+                ;[items[i], items[smallest]] = [items[smallest], items[i]]
+                i = smallest
+            }
+        }
+        return top
+    }
+}
+
+/**
+ * A {@link Heap} of numbers that also supports removing any value it
+ * contains. Removed values are counted, and only dropped once they reach the
+ * top; so the top is always a present value.
+ */
+export class LazyHeap {
+    private readonly heap: Heap<number>
+    private readonly removed: Map<number, number> = new Map()
+    private count = 0
+
+    constructor(lt: (a: number, b: number) => boolean) {
+        this.heap = new Heap(lt)
+    }
+
+    get size(): number {
+        return this.count
+    }
+
+    peek(): number | undefined {
+        return this.heap.peek()
+    }
+
+    push(value: number) {
+        this.heap.push(value)
+        this.count++
+    }
+
+    pop(): number | undefined {
+        const top = this.heap.pop()
+        if (top === undefined) {
+            return undefined
+        }
+        this.count--
+        this.prune()
+        return top
+    }
+
+    /**
+     * Removes a value, which must be in the heap.
+     */
+    remove(value: number) {
+        this.removed.set(value, (this.removed.get(value) ?? 0) + 1)
+        this.count--
+        this.prune()
+    }
+
+    private prune() {
+        for (;;) {
+            const top = this.heap.peek()
+            if (top === undefined) return
+
+            const count = this.removed.get(top)
+            if (count === undefined) return
+
+            this.heap.pop()
+            if (count === 1) {
+                this.removed.delete(top)
+            } else {
+                this.removed.set(top, count - 1)
+            }
+        }
+    }
+}
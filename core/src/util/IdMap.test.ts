@@ -37,6 +37,7 @@ test("LongMap", async (t) => {
         expected.sort((a, b) => a[0].localeCompare(b[0]));
 
         deepStrictEqual(actual, expected);
+        deepStrictEqual(map.size, ref.size);
       }),
       {
         numRuns: 10000,
@@ -3,6 +3,7 @@ import {Id} from "../core/simple_types"
 
 export class IdMap<T> {
     private map: Map<number, Map<number, T>> = new Map()
+    private count: number = 0
 
     get(key: Id): T | undefined {
         const m = this.map.get(key.asLong.high)
@@ -18,6 +19,9 @@ export class IdMap<T> {
             m = new Map()
             this.map.set(key.asLong.high, m)
         }
+        if(!m.has(key.asLong.low)) {
+            this.count++
+        }
         m.set(key.asLong.low, value)
     }
 
@@ -26,7 +30,9 @@ export class IdMap<T> {
         if(m === undefined) {
             return
         }
-        m.delete(key.asLong.low)
+        if(m.delete(key.asLong.low)) {
+            this.count--
+        }
 
         if(m.size === 0) {
             this.map.delete(key.asLong.high)
@@ -37,6 +43,10 @@ export class IdMap<T> {
         return this.map.size === 0
     }
 
+    get size(): number {
+        return this.count
+    }
+
     forEach(cb: (value: T, key: Id) => void) {
         for(const [id, v] of this.entries()) {
             cb(v, id)
@@ -78,6 +88,10 @@ export class IdSet {
         return this.inner.empty()
     }
 
+    get size(): number {
+        return this.inner.size
+    }
+
     forEach(cb: (value: Id) => void) {
         this.inner.forEach((_, key) => cb(key))
     }
@@ -0,0 +1,44 @@
+import test from "node:test";
+import { deepStrictEqual } from "node:assert";
+
+import fc from "fast-check";
+import { RankTree } from "./RankTree";
+
+test("RankTree", async () => {
+  await test("ref", () => {
+    return fc.assert(
+      fc.property(
+        fc.array(fc.tuple(fc.boolean(), fc.integer({ min: 0, max: 30 }))),
+        fc.integer({ min: 0, max: 30 }),
+        (calls, probe) => {
+          const tree = new RankTree<number>((a, b) => a - b);
+          const ref = new Set<number>();
+
+          for (const [insert, key] of calls) {
+            if (insert && !ref.has(key)) {
+              tree.insert(key);
+              ref.add(key);
+            } else if (!insert) {
+              deepStrictEqual(tree.delete(key), ref.delete(key));
+            }
+          }
+
+          const sorted = Array.from(ref).sort((a, b) => a - b);
+          deepStrictEqual(tree.size, sorted.length);
+          deepStrictEqual(
+            sorted.map((_, i) => tree.at(i)),
+            sorted
+          );
+          deepStrictEqual(tree.at(sorted.length), undefined);
+          deepStrictEqual(
+            tree.rank(probe),
+            sorted.filter((k) => k < probe).length
+          );
+        }
+      ),
+      {
+        numRuns: 10000,
+      }
+    );
+  });
+});
@@ -0,0 +1,128 @@
+type Node<K> = {
+    key: K
+    priority: number
+    size: number
+    left: Node<K> | undefined
+    right: Node<K> | undefined
+}
+
+/**
+ * A sorted set of unique keys, which can also find the rank of a key and the
+ * key at a rank in `O(log(n))`.
+ *
+ * Implemented as a treap, a binary search tree balanced by random priorities,
+ * with every node keeping the size of its subtree.
+ */
+export class RankTree<K> {
+    private root: Node<K> | undefined = undefined
+
+    constructor(private readonly compare: (a: K, b: K) => number) {}
+
+    get size(): number {
+        return size(this.root)
+    }
+
+    insert(key: K) {
+        const [lt, ge] = this.split(this.root, key)
+        const node: Node<K> = { key, priority: Math.random(), size: 1, left: undefined, right: undefined }
+        this.root = merge(merge(lt, node), ge)
+    }
+
+    delete(key: K): boolean {
+        const [lt, ge] = this.split(this.root, key)
+        const [eq, gt] = split(ge, (k) => this.compare(k, key) <= 0)
+        this.root = merge(lt, gt)
+        return eq !== undefined
+    }
+
+    /**
+     * The number of keys less than `key`.
+     */
+    rank(key: K): number {
+        return this.countBelow((k) => this.compare(k, key) < 0)
+    }
+
+    /**
+     * The number of keys satisfying `below`, which must also hold for every
+     * key smaller than one it holds for. Like `rank`, but for bounds that are
+     * not keys themselves.
+     */
+    countBelow(below: (key: K) => boolean): number {
+        let ret = 0
+        let node = this.root
+        while (node) {
+            if (below(node.key)) {
+                ret += size(node.left) + 1
+                node = node.right
+            } else {
+                node = node.left
+            }
+        }
+        return ret
+    }
+
+    /**
+     * The key with the given rank, counting from zero.
+     */
+    at(rank: number): K | undefined {
+        let node = this.root
+        while (node) {
+            const leftSize = size(node.left)
+            if (rank < leftSize) {
+                node = node.left
+            } else if (rank === leftSize) {
+                return node.key
+            } else {
+                rank -= leftSize + 1
+                node = node.right
+            }
+        }
+        return undefined
+    }
+
+    private split(node: Node<K> | undefined, key: K): [Node<K> | undefined, Node<K> | undefined] {
+        return split(node, (k) => this.compare(k, key) < 0)
+    }
+}
+
+function size<K>(node: Node<K> | undefined): number {
+    return node ? node.size : 0
+}
+
+function update<K>(node: Node<K>): Node<K> {
+    node.size = size(node.left) + size(node.right) + 1
+    return node
+}
+
+// Splits a tree into the keys satisfying `goesLeft`, and the rest. Keys
+// satisfying it must all be smaller than the ones that don't.
+function split<K>(
+    node: Node<K> | undefined,
+    goesLeft: (key: K) => boolean
+): [Node<K> | undefined, Node<K> | undefined] {
+    if (!node) {
+        return [undefined, undefined]
+    }
+    if (goesLeft(node.key)) {
+        const [left, right] = split(node.right, goesLeft)
+        node.right = left
+        return [update(node), right]
+    } else {
+        const [left, right] = split(node.left, goesLeft)
+        node.left = right
+        return [left, update(node)]
+    }
+}
+
+// Merges two trees, where all keys of `a` are smaller than the keys of `b`.
+function merge<K>(a: Node<K> | undefined, b: Node<K> | undefined): Node<K> | undefined {
+    if (!a) return b
+    if (!b) return a
+    if (a.priority > b.priority) {
+        a.right = merge(a.right, b)
+        return update(a)
+    } else {
+        b.left = merge(a, b.left)
+        return update(b)
+    }
+}
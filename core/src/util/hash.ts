@@ -0,0 +1,11 @@
+/**
+ * 32-bit FNV-1a hash of the UTF-16 code units of a string.
+ */
+export function fnv1a(str: string, seed: number = 0x811c9dc5): number {
+    let h = seed >>> 0
+    for (let i = 0; i < str.length; i++) {
+        h ^= str.charCodeAt(i)
+        h = Math.imul(h, 0x01000193) >>> 0
+    }
+    return h
+}
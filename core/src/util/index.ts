@@ -1,4 +1,8 @@
 export * from './IdMap'
+export * from './hash'
+export * from './Heap'
+export * from './Fenwick'
+export * from './RankTree'
 
 export function unreachable(x: never): never {
     throw new Error("invariant violation: unreachable")